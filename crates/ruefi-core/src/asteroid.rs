@@ -1,4 +1,5 @@
 use crate::rand::XorShift64;
+use crate::ship::Ship;
 use alloc::vec::Vec;
 use libm::{cosf, sinf, sqrtf};
 
@@ -8,14 +9,41 @@ extern crate alloc;
 pub struct Asteroid {
     pub x: f32,
     pub y: f32,
-    pub vx: f32,
-    pub vy: f32,
+    pub vx: f32, // pixels/second
+    pub vy: f32, // pixels/second
     pub radius: f32,
     pub base_angle: f32,  // orientation for the hexagon
     pub jitter: [f32; 6], // multiplicative per-vertex radius factors
     pub split_stage: u8,  // 2 -> splits into 4; 1 -> splits into 2; 0 -> destroyed on hit
 }
 
+/// Reported by [`Asteroid::handle_projectile_collisions`] for every asteroid destroyed by a projectile.
+#[derive(Clone, Copy, Debug)]
+pub struct AsteroidHit {
+    pub x: f32,
+    pub y: f32,
+    pub radius: f32,
+    pub split_stage: u8, // stage of the destroyed asteroid (2 = large, 0 = smallest)
+}
+
+/// Shortest vector from `(x0, y0)` to `(x1, y1)` on a torus of size (sw, sh).
+#[inline]
+fn torus_delta(x0: f32, y0: f32, x1: f32, y1: f32, sw: f32, sh: f32) -> (f32, f32) {
+    let mut dx = x1 - x0;
+    if dx > 0.5 * sw {
+        dx -= sw;
+    } else if dx < -0.5 * sw {
+        dx += sw;
+    }
+    let mut dy = y1 - y0;
+    if dy > 0.5 * sh {
+        dy -= sh;
+    } else if dy < -0.5 * sh {
+        dy += sh;
+    }
+    (dx, dy)
+}
+
 impl Asteroid {
     /// Average collision radius computed as mean of inscribed and circumscribed radii
     /// using the per-vertex jittered radii. This approximates the polygon with a circle.
@@ -57,8 +85,8 @@ impl Asteroid {
         for k in 0..count {
             let base_angle = rng.range_f32(0.0, 2.0 * core::f32::consts::PI);
             let mut jitter = [1.0f32; 6];
-            for j in 0..6 {
-                jitter[j] = rng.range_f32(0.75, 1.25);
+            for j in &mut jitter {
+                *j = rng.range_f32(0.75, 1.25);
             }
             // Velocity: parent's plus a small random kick, and a slight outward bias
             let dir = if count == 4 {
@@ -67,14 +95,14 @@ impl Asteroid {
             } else {
                 base_angle + (k as f32) * core::f32::consts::PI
             };
-            let speed = rng.range_f32(0.8, 2.5);
+            let speed = rng.range_f32(48.0, 150.0);
             let kick_vx = -sinf(dir) * speed;
             let kick_vy = cosf(dir) * speed;
             let vx = self.vx * (2.0f32 / 3.0f32) + kick_vx;
             let vy = self.vy * (2.0f32 / 3.0f32) + kick_vy;
-            // Position offset slightly along velocity to avoid immediate re-collision
-            let ox = vx * 0.5;
-            let oy = vy * 0.5;
+            // Position offset slightly along velocity (half a 60 Hz frame) to avoid immediate re-collision
+            let ox = vx * (0.5 / 60.0);
+            let oy = vy * (0.5 / 60.0);
             out.push(Asteroid {
                 x: self.x + ox,
                 y: self.y + oy,
@@ -91,7 +119,8 @@ impl Asteroid {
 
     /// Create a randomly shaped and placed asteroid, away from the screen center.
     /// Uses the provided RNG; world axes: X right, Y down.
-    pub fn random_spawn(rng: &mut XorShift64, sw: usize, sh: usize) -> Self {
+    /// `speed_scale` multiplies the random base speed, e.g. to make later waves faster.
+    pub fn random_spawn(rng: &mut XorShift64, sw: usize, sh: usize, speed_scale: f32) -> Self {
         let cx = (sw / 2) as f32;
         let cy = (sh / 2) as f32;
         let min_dist = 120.0f32; // keep spawn away from player start
@@ -109,15 +138,15 @@ impl Asteroid {
         };
 
         let radius = rng.range_f32(18.0, max_radius);
-        // small random velocity
-        let speed = rng.range_f32(0.5, 2.0);
+        // small random velocity, pixels/second
+        let speed = rng.range_f32(30.0, 120.0) * speed_scale;
         let dir = rng.range_f32(0.0, 2.0 * core::f32::consts::PI);
         let vx = -sinf(dir) * speed;
         let vy = cosf(dir) * speed;
         let base_angle = rng.range_f32(0.0, 2.0 * core::f32::consts::PI);
         let mut jitter = [1.0f32; 6];
-        for j in 0..6 {
-            jitter[j] = rng.range_f32(0.75, 1.25);
+        for j in &mut jitter {
+            *j = rng.range_f32(0.75, 1.25);
         }
 
         Self {
@@ -132,11 +161,11 @@ impl Asteroid {
         }
     }
 
-    /// Advance asteroid by velocity and wrap around screen bounds (toroidal world).
+    /// Advance asteroid by velocity over `dt` seconds and wrap around screen bounds (toroidal world).
     #[inline]
-    pub fn update(&mut self, sw: f32, sh: f32) {
-        self.x += self.vx;
-        self.y += self.vy;
+    pub fn update(&mut self, dt: f32, sw: f32, sh: f32) {
+        self.x += self.vx * dt;
+        self.y += self.vy * dt;
         if self.x < 0.0 {
             self.x += sw;
         }
@@ -260,15 +289,17 @@ impl Asteroid {
 
     /// Handle projectile vs asteroid collisions and splitting; mutates both lists.
     /// Uses toroidal shortest-vector distance and collision_radius approximation.
+    /// Returns one [`AsteroidHit`] per destroyed asteroid, e.g. for scoring.
     pub fn handle_projectile_collisions(
         asteroids: &mut Vec<Asteroid>,
         projectiles: &mut Vec<crate::projectile::Projectile>,
         rng: &mut XorShift64,
         sw: f32,
         sh: f32,
-    ) {
+    ) -> Vec<AsteroidHit> {
+        let mut hits: Vec<AsteroidHit> = Vec::new();
         if projectiles.is_empty() || asteroids.is_empty() {
-            return;
+            return hits;
         }
         let half_w = 0.5 * sw;
         let half_h = 0.5 * sh;
@@ -301,6 +332,12 @@ impl Asteroid {
                 if dx * dx + dy * dy <= r * r {
                     // hit: mark both dead, spawn children
                     proj_alive[pi] = false;
+                    hits.push(AsteroidHit {
+                        x: asteroids[ai].x,
+                        y: asteroids[ai].y,
+                        radius: asteroids[ai].radius,
+                        split_stage: asteroids[ai].split_stage,
+                    });
                     let kids = asteroids[ai].spawn_children(rng);
                     children.extend(kids);
                     ast_alive[ai] = false;
@@ -329,7 +366,275 @@ impl Asteroid {
                     asteroids.push(a);
                 }
             }
-            asteroids.extend(children.into_iter());
+            asteroids.extend(children);
+        }
+
+        hits
+    }
+
+    /// Check whether any asteroid overlaps the circle at (x, y) with the given radius.
+    /// Uses toroidal shortest-vector distance, e.g. to keep the respawn area clear.
+    pub fn area_clear(
+        asteroids: &[Asteroid],
+        x: f32,
+        y: f32,
+        radius: f32,
+        sw: f32,
+        sh: f32,
+    ) -> bool {
+        asteroids.iter().all(|a| {
+            let (dx, dy) = torus_delta(x, y, a.x, a.y, sw, sh);
+            let r = a.collision_radius() + radius;
+            dx * dx + dy * dy > r * r
+        })
+    }
+
+    /// Handle ship vs asteroid collisions; destroys the ship and splits the asteroid it hit.
+    /// Returns `true` if the ship was destroyed. Invulnerable or dead ships are ignored.
+    pub fn handle_ship_collision(
+        asteroids: &mut Vec<Asteroid>,
+        ship: &mut Ship,
+        rng: &mut XorShift64,
+        sw: f32,
+        sh: f32,
+    ) -> bool {
+        if !ship.is_vulnerable() {
+            return false;
+        }
+        let ship_r = ship.collision_radius();
+        let hit = asteroids.iter().position(|a| {
+            let (dx, dy) = torus_delta(ship.x, ship.y, a.x, a.y, sw, sh);
+            let r = a.collision_radius() + ship_r;
+            dx * dx + dy * dy <= r * r
+        });
+        let Some(ai) = hit else {
+            return false;
+        };
+
+        ship.destroy();
+        let rock = asteroids.swap_remove(ai);
+        asteroids.extend(rock.spawn_children(rng));
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::projectile::Projectile;
+
+    const SW: f32 = 800.0;
+    const SH: f32 = 600.0;
+
+    fn rock(x: f32, y: f32, vx: f32, vy: f32, radius: f32, split_stage: u8) -> Asteroid {
+        Asteroid {
+            x,
+            y,
+            vx,
+            vy,
+            radius,
+            base_angle: 0.0,
+            jitter: [1.0; 6],
+            split_stage,
+        }
+    }
+
+    fn bullet(x: f32, y: f32) -> Projectile {
+        Projectile {
+            x,
+            y,
+            vx: 0.0,
+            vy: 0.0,
+        }
+    }
+
+    #[test]
+    fn update_wraps_around_all_edges() {
+        let mut a = rock(799.0, 1.0, 120.0, -120.0, 10.0, 2);
+        a.update(0.5, SW, SH);
+        assert!((a.x - 59.0).abs() < 1e-3, "x = {}", a.x);
+        assert!((a.y - 541.0).abs() < 1e-3, "y = {}", a.y);
+    }
+
+    #[test]
+    fn torus_delta_takes_shortest_path() {
+        let (dx, dy) = torus_delta(790.0, 590.0, 10.0, 10.0, SW, SH);
+        assert_eq!((dx, dy), (20.0, 20.0));
+        let (dx, dy) = torus_delta(10.0, 10.0, 790.0, 590.0, SW, SH);
+        assert_eq!((dx, dy), (-20.0, -20.0));
+        let (dx, dy) = torus_delta(100.0, 100.0, 150.0, 50.0, SW, SH);
+        assert_eq!((dx, dy), (50.0, -50.0));
+    }
+
+    #[test]
+    fn collision_radius_uses_jitter_extremes() {
+        let mut a = rock(0.0, 0.0, 0.0, 0.0, 10.0, 2);
+        a.jitter = [0.8, 1.0, 1.2, 1.0, 1.0, 1.0];
+        assert!((a.collision_radius() - 10.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn resolve_collisions_separates_and_bounces() {
+        let mut rocks = [
+            rock(100.0, 100.0, 60.0, 0.0, 20.0, 2),
+            rock(130.0, 100.0, -60.0, 0.0, 20.0, 2),
+        ];
+        Asteroid::resolve_collisions(&mut rocks, SW, SH);
+        assert!(rocks[0].x < 100.0 && rocks[1].x > 130.0);
+        assert!(rocks[0].vx < 0.0 && rocks[1].vx > 0.0);
+        // Equal masses: momentum is conserved
+        assert!((rocks[0].vx + rocks[1].vx).abs() < 1e-3);
+    }
+
+    #[test]
+    fn resolve_collisions_works_across_the_wrap_boundary() {
+        let mut rocks = [
+            rock(5.0, 300.0, -60.0, 0.0, 20.0, 2),
+            rock(795.0, 300.0, 60.0, 0.0, 20.0, 2),
+        ];
+        Asteroid::resolve_collisions(&mut rocks, SW, SH);
+        assert!(rocks[0].vx > 0.0 && rocks[1].vx < 0.0);
+        for a in &rocks {
+            assert!(a.x >= 0.0 && a.x < SW);
+        }
+    }
+
+    #[test]
+    fn resolve_collisions_ignores_separated_asteroids() {
+        let mut rocks = [
+            rock(100.0, 100.0, 60.0, 0.0, 20.0, 2),
+            rock(200.0, 100.0, -60.0, 0.0, 20.0, 2),
+        ];
+        Asteroid::resolve_collisions(&mut rocks, SW, SH);
+        assert_eq!((rocks[0].x, rocks[0].vx), (100.0, 60.0));
+        assert_eq!((rocks[1].x, rocks[1].vx), (200.0, -60.0));
+    }
+
+    #[test]
+    fn spawn_children_follows_split_stage() {
+        let mut rng = XorShift64::new(1);
+        let large = rock(100.0, 100.0, 30.0, 0.0, 40.0, 2);
+        let kids = large.spawn_children(&mut rng);
+        assert_eq!(kids.len(), 4);
+        for k in &kids {
+            assert_eq!(k.split_stage, 1);
+            assert_eq!(k.radius, 20.0);
+        }
+
+        let medium = rock(100.0, 100.0, 30.0, 0.0, 20.0, 1);
+        let kids = medium.spawn_children(&mut rng);
+        assert_eq!(kids.len(), 2);
+        assert!(kids.iter().all(|k| k.split_stage == 0 && k.radius == 10.0));
+
+        let small = rock(100.0, 100.0, 30.0, 0.0, 10.0, 0);
+        assert!(small.spawn_children(&mut rng).is_empty());
+    }
+
+    #[test]
+    fn spawn_children_keeps_a_minimum_radius() {
+        let mut rng = XorShift64::new(1);
+        let tiny = rock(100.0, 100.0, 0.0, 0.0, 5.0, 1);
+        assert!(
+            tiny.spawn_children(&mut rng)
+                .iter()
+                .all(|k| k.radius == 4.0)
+        );
+    }
+
+    #[test]
+    fn projectile_hit_splits_asteroid_and_reports_it() {
+        let mut rng = XorShift64::new(7);
+        let mut rocks = alloc::vec![
+            rock(100.0, 100.0, 0.0, 0.0, 30.0, 2),
+            rock(400.0, 400.0, 0.0, 0.0, 30.0, 1),
+        ];
+        let mut shots = alloc::vec![bullet(110.0, 100.0), bullet(700.0, 100.0)];
+        let hits = Asteroid::handle_projectile_collisions(&mut rocks, &mut shots, &mut rng, SW, SH);
+
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].split_stage, 2);
+        assert_eq!((hits[0].x, hits[0].y, hits[0].radius), (100.0, 100.0, 30.0));
+        // Remaining projectile is the one that missed
+        assert_eq!(shots.len(), 1);
+        assert_eq!(shots[0].x, 700.0);
+        // Untouched asteroid plus four children
+        assert_eq!(rocks.len(), 5);
+        assert_eq!(rocks[0].split_stage, 1);
+        assert_eq!(rocks[0].x, 400.0);
+        assert_eq!(rocks.iter().filter(|a| a.radius == 15.0).count(), 4);
+    }
+
+    #[test]
+    fn projectile_hits_across_the_wrap_boundary() {
+        let mut rng = XorShift64::new(7);
+        let mut rocks = alloc::vec![rock(795.0, 300.0, 0.0, 0.0, 20.0, 0)];
+        let mut shots = alloc::vec![bullet(5.0, 300.0)];
+        let hits = Asteroid::handle_projectile_collisions(&mut rocks, &mut shots, &mut rng, SW, SH);
+        assert_eq!(hits.len(), 1);
+        assert!(rocks.is_empty());
+        assert!(shots.is_empty());
+    }
+
+    #[test]
+    fn one_projectile_destroys_at_most_one_asteroid() {
+        let mut rng = XorShift64::new(7);
+        let mut rocks = alloc::vec![
+            rock(100.0, 100.0, 0.0, 0.0, 30.0, 0),
+            rock(105.0, 100.0, 0.0, 0.0, 30.0, 0),
+        ];
+        let mut shots = alloc::vec![bullet(102.0, 100.0)];
+        let hits = Asteroid::handle_projectile_collisions(&mut rocks, &mut shots, &mut rng, SW, SH);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(rocks.len(), 1);
+    }
+
+    #[test]
+    fn ship_collision_destroys_vulnerable_ship() {
+        let mut rng = XorShift64::new(3);
+        let mut ship = Ship::new(800, 600);
+        let mut rocks = alloc::vec![rock(420.0, 300.0, 0.0, 0.0, 20.0, 1)];
+        assert!(Asteroid::handle_ship_collision(
+            &mut rocks, &mut ship, &mut rng, SW, SH
+        ));
+        assert!(!ship.alive);
+        assert_eq!(ship.lives, crate::ship::START_LIVES - 1);
+        // The asteroid that hit the ship is split as well
+        assert_eq!(rocks.len(), 2);
+        assert!(rocks.iter().all(|a| a.split_stage == 0));
+    }
+
+    #[test]
+    fn ship_collision_ignores_invulnerable_ship() {
+        let mut rng = XorShift64::new(3);
+        let mut ship = Ship::new(800, 600);
+        ship.destroy();
+        ship.respawn(800, 600);
+        let mut rocks = alloc::vec![rock(400.0, 300.0, 0.0, 0.0, 20.0, 1)];
+        assert!(!Asteroid::handle_ship_collision(
+            &mut rocks, &mut ship, &mut rng, SW, SH
+        ));
+        assert!(ship.alive);
+        assert_eq!(rocks.len(), 1);
+    }
+
+    #[test]
+    fn area_clear_detects_nearby_asteroids() {
+        let rocks = [rock(10.0, 300.0, 0.0, 0.0, 20.0, 2)];
+        assert!(Asteroid::area_clear(&rocks, 400.0, 300.0, 100.0, SW, SH));
+        assert!(!Asteroid::area_clear(&rocks, 100.0, 300.0, 100.0, SW, SH));
+        // Wrapped distance: 10 -> 750 is only 60 pixels apart
+        assert!(!Asteroid::area_clear(&rocks, 750.0, 300.0, 50.0, SW, SH));
+    }
+
+    #[test]
+    fn random_spawn_keeps_away_from_the_center() {
+        let mut rng = XorShift64::new(42);
+        for _ in 0..200 {
+            let a = Asteroid::random_spawn(&mut rng, 800, 600, 1.0);
+            let (dx, dy) = (a.x - 400.0, a.y - 300.0);
+            assert!(dx * dx + dy * dy >= 120.0 * 120.0);
+            assert!(a.x >= 0.0 && a.x < SW && a.y >= 0.0 && a.y < SH);
+            assert_eq!(a.split_stage, 2);
         }
     }
 }
@@ -0,0 +1,494 @@
+use crate::controls::{Action, Bindings, KEYS_PER_ACTION, RESERVED_KEYS};
+use crate::input::KeyId;
+use crate::ship::{FlightModel, START_LIVES, Ship};
+use crate::wave::WaveConfig;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+
+extern crate alloc;
+
+/// Range of the projectile speed, in pixels/second; also bounds the in-game adjustment.
+pub const PROJECTILE_SPEED_MIN: f32 = 120.0;
+pub const PROJECTILE_SPEED_MAX: f32 = 3000.0;
+
+/// Game tuning read from `ruefi.cfg`; every setting falls back to its default.
+///
+/// The file is a TOML subset: `key = value` lines, `#` comments and `[section]`
+/// headers that prefix the following keys, so `[ship]` followed by `thrust = 120`
+/// is the same as `ship.thrust = 120`.
+#[derive(Clone)]
+pub struct Config {
+    pub ship_flight_model: FlightModel,
+    pub ship_thrust: f32,          // arcade: speed when thrusting, pixels/second
+    pub ship_friction: f32,        // arcade: exponential speed decay rate, 1/second
+    pub ship_acceleration: f32,    // Newtonian: thrust acceleration, pixels/second²
+    pub ship_drag: f32,            // Newtonian: exponential velocity decay rate, 1/second
+    pub ship_max_speed: f32,       // Newtonian: speed cap, pixels/second
+    pub ship_reverse_thrust: bool, // Newtonian: whether reverse thrust brakes
+    pub ship_rot_speed: f32,       // radians per second
+    pub ship_lives: u32,           // ships per game
+    pub max_projectiles: usize,    // projectiles alive at the same time
+    pub projectile_speed: f32,     // initial projectile speed, pixels/second
+    pub projectile_len: f32,       // visible projectile length in pixels
+    pub waves: WaveConfig,
+    pub video_width: usize,  // preferred horizontal resolution
+    pub video_height: usize, // preferred vertical resolution
+    pub line_width: f32,     // width of vector outlines in pixels
+    pub anti_aliasing: bool, // smooth edges of vector outlines
+    pub persistence: bool,   // phosphor trails behind moving objects
+    pub bloom: bool,         // glow around bright lines
+    pub scanlines: bool,     // darken every other row
+    pub controls: Bindings,  // keys of the game actions
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        let ship = Ship::new(0, 0);
+        Self {
+            ship_flight_model: ship.flight_model,
+            ship_thrust: ship.thrust,
+            ship_friction: ship.friction,
+            ship_acceleration: ship.acceleration,
+            ship_drag: ship.drag,
+            ship_max_speed: ship.max_speed,
+            ship_reverse_thrust: ship.reverse_thrust,
+            ship_rot_speed: ship.rot_speed,
+            ship_lives: START_LIVES,
+            max_projectiles: 100,
+            projectile_speed: 720.0,
+            projectile_len: 5.0,
+            waves: WaveConfig::default(),
+            video_width: 1280,
+            video_height: 720,
+            line_width: 1.5,
+            anti_aliasing: true,
+            persistence: false,
+            bloom: false,
+            scanlines: false,
+            controls: Bindings::default(),
+        }
+    }
+}
+
+/// A problem with a single line of the configuration file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConfigError {
+    pub line: usize, // 1-based line number
+    pub kind: ConfigErrorKind,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConfigErrorKind {
+    MissingEquals,
+    BadSection,
+    UnknownKey(String),
+    InvalidNumber(String),
+    InvalidFlag(String),
+    InvalidKey(String),
+    ReservedKey(String),
+    DuplicateKey(String),
+    TooManyKeys,
+    InvalidChoice {
+        value: String,
+        choices: &'static str,
+    },
+    OutOfRange {
+        min: String,
+        max: String,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            ConfigErrorKind::MissingEquals => f.write_str("expected `key = value`"),
+            ConfigErrorKind::BadSection => f.write_str("malformed `[section]` header"),
+            ConfigErrorKind::UnknownKey(key) => write!(f, "unknown key `{key}`"),
+            ConfigErrorKind::InvalidNumber(value) => write!(f, "`{value}` is not a valid number"),
+            ConfigErrorKind::InvalidFlag(value) => write!(f, "`{value}` is not `true` or `false`"),
+            ConfigErrorKind::InvalidKey(value) => write!(f, "`{value}` is not a key name"),
+            ConfigErrorKind::ReservedKey(value) => write!(f, "`{value}` cannot be rebound"),
+            ConfigErrorKind::DuplicateKey(value) => write!(f, "`{value}` is bound more than once"),
+            ConfigErrorKind::TooManyKeys => {
+                write!(
+                    f,
+                    "at most {KEYS_PER_ACTION} keys can be bound to an action"
+                )
+            }
+            ConfigErrorKind::InvalidChoice { value, choices } => {
+                write!(f, "`{value}` is not one of {choices}")
+            }
+            ConfigErrorKind::OutOfRange { min, max } => {
+                write!(f, "value must be between {min} and {max}")
+            }
+        }
+    }
+}
+
+impl Config {
+    /// Parse the configuration file. Malformed lines are reported and skipped,
+    /// leaving the affected settings at their defaults.
+    pub fn parse(text: &str) -> (Self, Vec<ConfigError>) {
+        let mut config = Self::default();
+        let mut errors = Vec::new();
+        let mut section = String::new();
+        let mut bound = [false; Action::ALL.len()]; // actions bound in the file so far
+
+        for (index, raw) in text.lines().enumerate() {
+            let line = raw.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let error = |kind| ConfigError {
+                line: index + 1,
+                kind,
+            };
+
+            if let Some(header) = line.strip_prefix('[') {
+                match header.strip_suffix(']').map(str::trim) {
+                    Some(name) if !name.is_empty() => section = name.to_string(),
+                    _ => errors.push(error(ConfigErrorKind::BadSection)),
+                }
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                errors.push(error(ConfigErrorKind::MissingEquals));
+                continue;
+            };
+            let (key, value) = (key.trim(), value.trim());
+            let key = if section.is_empty() {
+                key.to_string()
+            } else {
+                alloc::format!("{section}.{key}")
+            };
+            let result = if key.starts_with("controls.") {
+                config.set_controls(&key, value, &mut bound)
+            } else {
+                config.set(&key, value)
+            };
+            if let Err(kind) = result {
+                errors.push(error(kind));
+            }
+        }
+
+        (config, errors)
+    }
+
+    /// Bind the keys of an action. Like on the controls screen, a key moves away from an
+    /// action that still has its default keys; one bound to another action in the file
+    /// is refused.
+    fn set_controls(
+        &mut self,
+        key: &str,
+        value: &str,
+        bound: &mut [bool; Action::ALL.len()],
+    ) -> Result<(), ConfigErrorKind> {
+        let action = key
+            .strip_prefix("controls.")
+            .and_then(Action::from_name)
+            .ok_or_else(|| ConfigErrorKind::UnknownKey(key.to_string()))?;
+        let keys = keys(value)?;
+        for key in keys.into_iter().flatten() {
+            if let Some(other) = self.controls.action(key)
+                && other != action
+                && bound[other as usize]
+            {
+                return Err(ConfigErrorKind::DuplicateKey(alloc::format!("{key}")));
+            }
+        }
+        self.controls.clear(action);
+        for key in keys.into_iter().rev().flatten() {
+            self.controls.bind(action, key);
+        }
+        bound[action as usize] = true;
+        Ok(())
+    }
+
+    /// Apply a single setting after validating its value.
+    fn set(&mut self, key: &str, value: &str) -> Result<(), ConfigErrorKind> {
+        let w = &mut self.waves;
+        match key {
+            "ship.flight_model" => self.ship_flight_model = flight_model(value)?,
+            "ship.thrust" => self.ship_thrust = number(value, 10.0, 1000.0)?,
+            "ship.friction" => self.ship_friction = number(value, 0.0, 60.0)?,
+            "ship.acceleration" => self.ship_acceleration = number(value, 10.0, 5000.0)?,
+            "ship.drag" => self.ship_drag = number(value, 0.0, 10.0)?,
+            "ship.max_speed" => self.ship_max_speed = number(value, 10.0, 2000.0)?,
+            "ship.reverse_thrust" => self.ship_reverse_thrust = flag(value)?,
+            "ship.rot_speed" => self.ship_rot_speed = number(value, 0.5, 20.0)?,
+            "ship.lives" => self.ship_lives = number(value, 1, 9)?,
+            "projectile.max" => self.max_projectiles = number(value, 1, 1000)?,
+            "projectile.speed" => {
+                self.projectile_speed = number(value, PROJECTILE_SPEED_MIN, PROJECTILE_SPEED_MAX)?;
+            }
+            "projectile.length" => self.projectile_len = number(value, 1.0, 50.0)?,
+            "wave.first_count" => w.first_count = number(value, 1, 50)?,
+            "wave.count_step" => w.count_step = number(value, 0, 10)?,
+            "wave.max_count" => w.max_count = number(value, 1, 50)?,
+            "wave.first_speed" => w.first_speed = number(value, 0.1, 10.0)?,
+            "wave.speed_step" => w.speed_step = number(value, 0.0, 2.0)?,
+            "wave.max_speed" => w.max_speed = number(value, 0.1, 10.0)?,
+            "wave.banner_time" => w.banner_time = number(value, 0.0, 10.0)?,
+            "video.width" => self.video_width = number(value, 320, 7680)?,
+            "video.height" => self.video_height = number(value, 200, 4320)?,
+            "video.line_width" => self.line_width = number(value, 1.0, 8.0)?,
+            "video.anti_aliasing" => self.anti_aliasing = flag(value)?,
+            "video.persistence" => self.persistence = flag(value)?,
+            "video.bloom" => self.bloom = flag(value)?,
+            "video.scanlines" => self.scanlines = flag(value)?,
+            _ => return Err(ConfigErrorKind::UnknownKey(key.to_string())),
+        }
+        Ok(())
+    }
+
+    /// Construct a ship centered on screen with the configured tuning.
+    pub fn ship(&self, sw: usize, sh: usize) -> Ship {
+        let mut ship = Ship::new(sw, sh);
+        ship.flight_model = self.ship_flight_model;
+        ship.thrust = self.ship_thrust;
+        ship.friction = self.ship_friction;
+        ship.acceleration = self.ship_acceleration;
+        ship.drag = self.ship_drag;
+        ship.max_speed = self.ship_max_speed;
+        ship.reverse_thrust = self.ship_reverse_thrust;
+        ship.rot_speed = self.ship_rot_speed;
+        ship.lives = self.ship_lives;
+        ship
+    }
+}
+
+/// Parse a boolean flag.
+fn flag(value: &str) -> Result<bool, ConfigErrorKind> {
+    match value {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        _ => Err(ConfigErrorKind::InvalidFlag(value.to_string())),
+    }
+}
+
+/// Parse a comma-separated list of key names, or `none`.
+fn keys(value: &str) -> Result<[Option<KeyId>; KEYS_PER_ACTION], ConfigErrorKind> {
+    let mut keys = [None; KEYS_PER_ACTION];
+    if value == "none" {
+        return Ok(keys);
+    }
+    for (i, name) in value.split(',').map(str::trim).enumerate() {
+        let key = KeyId::from_name(name).ok_or(ConfigErrorKind::InvalidKey(name.to_string()))?;
+        if RESERVED_KEYS.contains(&key) {
+            return Err(ConfigErrorKind::ReservedKey(name.to_string()));
+        }
+        if keys.contains(&Some(key)) {
+            return Err(ConfigErrorKind::DuplicateKey(name.to_string()));
+        }
+        *keys.get_mut(i).ok_or(ConfigErrorKind::TooManyKeys)? = Some(key);
+    }
+    Ok(keys)
+}
+
+/// Replace a section of a configuration file: its `[name]` block and any `name.`-prefixed
+/// keys outside of sections are removed, and `body` is appended under a new `[name]` header.
+/// Everything else, including comments, is kept as is.
+pub fn replace_section(text: &str, name: &str, body: &str) -> String {
+    let prefix = alloc::format!("{name}.");
+    let mut out = String::new();
+    let mut in_section = false;
+    let mut top_level = true;
+    for line in text.lines() {
+        let content = line.split('#').next().unwrap_or("").trim();
+        if let Some(header) = content.strip_prefix('[') {
+            in_section = header.strip_suffix(']').map(str::trim) == Some(name);
+            top_level = false;
+            if in_section {
+                continue;
+            }
+        }
+        if in_section || (top_level && content.starts_with(&prefix)) {
+            continue;
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    while out.ends_with("\n\n") {
+        out.pop();
+    }
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    out.push('[');
+    out.push_str(name);
+    out.push_str("]\n");
+    out.push_str(body);
+    out
+}
+
+/// Parse a flight model name.
+fn flight_model(value: &str) -> Result<FlightModel, ConfigErrorKind> {
+    match value {
+        "newtonian" => Ok(FlightModel::Newtonian),
+        "arcade" => Ok(FlightModel::Arcade),
+        _ => Err(ConfigErrorKind::InvalidChoice {
+            value: value.to_string(),
+            choices: "`newtonian` or `arcade`",
+        }),
+    }
+}
+
+/// Parse a number and check that it lies within `min..=max`.
+fn number<T>(value: &str, min: T, max: T) -> Result<T, ConfigErrorKind>
+where
+    T: FromStr + PartialOrd + fmt::Display,
+{
+    let n: T = value
+        .parse()
+        .map_err(|_| ConfigErrorKind::InvalidNumber(value.to_string()))?;
+    // Also rejects NaN, which compares false to everything
+    if !(&min..=&max).contains(&&n) {
+        return Err(ConfigErrorKind::OutOfRange {
+            min: min.to_string(),
+            max: max.to_string(),
+        });
+    }
+    Ok(n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_file_yields_defaults() {
+        let (config, errors) = Config::parse("");
+        assert!(errors.is_empty());
+        assert_eq!(config.max_projectiles, Config::default().max_projectiles);
+    }
+
+    #[test]
+    fn parses_dotted_keys_sections_and_comments() {
+        let text = "# tuning\nship.thrust = 120 # faster\n\n[wave]\nfirst_count=3\n[video]\nwidth = 800\nanti_aliasing = false\n";
+        let (config, errors) = Config::parse(text);
+        assert!(errors.is_empty(), "{errors:?}");
+        assert_eq!(config.ship_thrust, 120.0);
+        assert_eq!(config.waves.first_count, 3);
+        assert_eq!(config.video_width, 800);
+        assert!(!config.anti_aliasing);
+        assert_eq!(config.ship(800, 600).thrust, 120.0);
+    }
+
+    #[test]
+    fn malformed_lines_are_reported_and_skipped() {
+        let text =
+            "ship.lives = 5\nnonsense\nship.lives = 0\nship.thrust = fast\n[oops\nwarp = 9\n";
+        let (config, errors) = Config::parse(text);
+        assert_eq!(config.ship_lives, 5);
+        assert_eq!(config.ship_thrust, Config::default().ship_thrust);
+
+        let lines: Vec<usize> = errors.iter().map(|e| e.line).collect();
+        assert_eq!(lines, [2, 3, 4, 5, 6]);
+        assert_eq!(errors[0].kind, ConfigErrorKind::MissingEquals);
+        assert_eq!(
+            errors[1].to_string(),
+            "line 3: value must be between 1 and 9"
+        );
+        assert_eq!(
+            errors[2].kind,
+            ConfigErrorKind::InvalidNumber("fast".to_string())
+        );
+        assert_eq!(errors[3].kind, ConfigErrorKind::BadSection);
+        assert_eq!(errors[4].to_string(), "line 6: unknown key `warp`");
+    }
+
+    #[test]
+    fn non_finite_numbers_are_rejected() {
+        let (config, errors) = Config::parse("ship.thrust = nan\nship.drag = inf\n");
+        assert_eq!(config.ship_thrust, Config::default().ship_thrust);
+        assert_eq!(config.ship_drag, Config::default().ship_drag);
+        assert_eq!(errors.len(), 2);
+        assert!(
+            errors
+                .iter()
+                .all(|e| matches!(e.kind, ConfigErrorKind::OutOfRange { .. }))
+        );
+    }
+
+    #[test]
+    fn flight_model_is_selectable() {
+        let text = "[ship]\nflight_model = arcade\nreverse_thrust = false\n";
+        let (config, errors) = Config::parse(text);
+        assert!(errors.is_empty(), "{errors:?}");
+        let ship = config.ship(800, 600);
+        assert_eq!(ship.flight_model, FlightModel::Arcade);
+        assert!(!ship.reverse_thrust);
+
+        let (_, errors) = Config::parse("ship.flight_model = warp\n");
+        assert_eq!(
+            errors[0].to_string(),
+            "line 1: `warp` is not one of `newtonian` or `arcade`"
+        );
+    }
+
+    #[test]
+    fn controls_are_read_from_their_section() {
+        let text = "[controls]\nfire = x, space\nthrust = w\npause = none\n";
+        let (config, errors) = Config::parse(text);
+        assert!(errors.is_empty(), "{errors:?}");
+        let keys = config.controls.keys(Action::Fire);
+        assert_eq!(keys, [Some(KeyId::Char('x')), Some(KeyId::Char(' '))]);
+        assert_eq!(config.controls.keys(Action::Pause), [None, None]);
+
+        let text = "[controls]\nfire = warp\nfire = esc\nfire = x, y, z\nwarp = x\n";
+        let (_, errors) = Config::parse(text);
+        let messages: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+        assert_eq!(
+            messages,
+            [
+                "line 2: `warp` is not a key name",
+                "line 3: `esc` cannot be rebound",
+                "line 4: at most 2 keys can be bound to an action",
+                "line 5: unknown key `controls.warp`",
+            ]
+        );
+    }
+
+    #[test]
+    fn keys_bound_twice_are_rejected() {
+        let text = "[controls]\nfire = x, x\nthrust = space\nreverse = space\n";
+        let (config, errors) = Config::parse(text);
+        let messages: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+        assert_eq!(
+            messages,
+            [
+                "line 2: `x` is bound more than once",
+                "line 4: `SPACE` is bound more than once",
+            ]
+        );
+        // The default key of fire moves to thrust, but not on to reverse
+        let bindings = &config.controls;
+        assert_eq!(bindings.action(KeyId::Char(' ')), Some(Action::Thrust));
+        assert_eq!(bindings.keys(Action::Fire), [None, None]);
+        assert_eq!(
+            bindings.keys(Action::Reverse),
+            Bindings::default().keys(Action::Reverse)
+        );
+    }
+
+    #[test]
+    fn saving_a_section_keeps_the_rest_of_the_file() {
+        let text = "# mine\ncontrols.fire = x\n[controls]\nthrust = w\n\n[video]\nbloom = true\n";
+        let saved = replace_section(text, "controls", "fire = space\n");
+        assert_eq!(
+            saved,
+            "# mine\n[video]\nbloom = true\n\n[controls]\nfire = space\n"
+        );
+        assert_eq!(
+            replace_section("", "controls", "fire = space\n"),
+            "[controls]\nfire = space\n"
+        );
+        let (config, errors) = Config::parse(&saved);
+        assert!(errors.is_empty(), "{errors:?}");
+        assert!(config.bloom);
+    }
+}
@@ -0,0 +1,404 @@
+//! Rebindable controls: the keys that trigger each game action, and the controls screen
+//! that edits them.
+
+use crate::input::{KeyId, SCAN_DELETE, SCAN_DOWN, SCAN_ESCAPE, SCAN_LEFT, SCAN_RIGHT, SCAN_UP};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+/// Keys that can be bound to a single action.
+pub const KEYS_PER_ACTION: usize = 2;
+
+/// Keys with fixed functions (menus, display toggles, exit); they cannot be bound.
+pub const RESERVED_KEYS: [KeyId; 8] = [
+    KeyId::Scan(SCAN_ESCAPE),
+    KeyId::Char('\r'),
+    KeyId::Char('c'),
+    KeyId::Char('v'),
+    KeyId::Char('p'),
+    KeyId::Char('b'),
+    KeyId::Char('l'),
+    KeyId::Char('f'),
+];
+
+/// Something the player can do with a key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    RotateLeft,
+    RotateRight,
+    Thrust,
+    Reverse,
+    Fire,
+    Hyperspace,
+    Pause,
+    SpeedUp,
+    SpeedDown,
+}
+
+impl Action {
+    pub const ALL: [Action; 9] = [
+        Action::RotateLeft,
+        Action::RotateRight,
+        Action::Thrust,
+        Action::Reverse,
+        Action::Fire,
+        Action::Hyperspace,
+        Action::Pause,
+        Action::SpeedUp,
+        Action::SpeedDown,
+    ];
+
+    /// Key of the action in the `[controls]` section of `ruefi.cfg`.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Action::RotateLeft => "rotate_left",
+            Action::RotateRight => "rotate_right",
+            Action::Thrust => "thrust",
+            Action::Reverse => "reverse",
+            Action::Fire => "fire",
+            Action::Hyperspace => "hyperspace",
+            Action::Pause => "pause",
+            Action::SpeedUp => "speed_up",
+            Action::SpeedDown => "speed_down",
+        }
+    }
+
+    /// Label on the controls screen.
+    pub const fn label(self) -> &'static str {
+        match self {
+            Action::RotateLeft => "ROTATE LEFT",
+            Action::RotateRight => "ROTATE RIGHT",
+            Action::Thrust => "THRUST",
+            Action::Reverse => "REVERSE",
+            Action::Fire => "FIRE",
+            Action::Hyperspace => "HYPERSPACE",
+            Action::Pause => "PAUSE",
+            Action::SpeedUp => "SHOT SPEED UP",
+            Action::SpeedDown => "SHOT SPEED DOWN",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|a| a.name() == name)
+    }
+}
+
+/// Predefined sets of bindings.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Preset {
+    /// Arrow keys steer and thrust, Space fires.
+    Arrows,
+    /// W, A, S and D steer and thrust, Space fires.
+    Wasd,
+}
+
+/// The keys bound to each action.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Bindings {
+    keys: [[Option<KeyId>; KEYS_PER_ACTION]; Action::ALL.len()], // by action, primary key first
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Self::preset(Preset::Arrows)
+    }
+}
+
+impl Bindings {
+    pub fn preset(preset: Preset) -> Self {
+        let (left, right, thrust, reverse, hyperspace) = match preset {
+            Preset::Arrows => (
+                KeyId::Scan(SCAN_LEFT),
+                KeyId::Scan(SCAN_RIGHT),
+                KeyId::Scan(SCAN_UP),
+                KeyId::Scan(SCAN_DOWN),
+                KeyId::Char('h'),
+            ),
+            Preset::Wasd => (
+                KeyId::Char('a'),
+                KeyId::Char('d'),
+                KeyId::Char('w'),
+                KeyId::Char('s'),
+                KeyId::Char('e'),
+            ),
+        };
+        let mut bindings = Self {
+            keys: [[None; KEYS_PER_ACTION]; Action::ALL.len()],
+        };
+        for (action, key) in [
+            (Action::RotateLeft, left),
+            (Action::RotateRight, right),
+            (Action::Thrust, thrust),
+            (Action::Reverse, reverse),
+            (Action::Fire, KeyId::Char(' ')),
+            (Action::Hyperspace, hyperspace),
+            (Action::Pause, KeyId::Char('\t')),
+            (Action::SpeedUp, KeyId::Char(']')),
+            (Action::SpeedDown, KeyId::Char('[')),
+        ] {
+            bindings.keys[action as usize][0] = Some(key);
+        }
+        bindings
+    }
+
+    /// The keys bound to an action, primary key first.
+    #[inline]
+    pub fn keys(&self, action: Action) -> [Option<KeyId>; KEYS_PER_ACTION] {
+        self.keys[action as usize]
+    }
+
+    /// The action a key is bound to, if any.
+    pub fn action(&self, key: KeyId) -> Option<Action> {
+        Action::ALL
+            .into_iter()
+            .find(|&a| self.keys(a).contains(&Some(key)))
+    }
+
+    /// Whether any key of the action satisfies `pred`.
+    pub fn any_key(&self, action: Action, pred: impl Fn(KeyId) -> bool) -> bool {
+        self.keys(action).into_iter().flatten().any(pred)
+    }
+
+    /// Make `key` the primary key of `action`; the previous primary key becomes the secondary
+    /// one, and the key is unbound from other actions. Reserved keys are refused.
+    pub fn bind(&mut self, action: Action, key: KeyId) -> bool {
+        if RESERVED_KEYS.contains(&key) {
+            return false;
+        }
+        for keys in &mut self.keys {
+            for slot in keys.iter_mut().filter(|k| **k == Some(key)) {
+                *slot = None;
+            }
+        }
+        let keys = &mut self.keys[action as usize];
+        if keys[0].is_some() {
+            keys[1] = keys[0];
+        }
+        keys[0] = Some(key);
+        true
+    }
+
+    /// Unbind all keys of an action.
+    pub fn clear(&mut self, action: Action) {
+        self.keys[action as usize] = [None; KEYS_PER_ACTION];
+    }
+
+    /// The keys of an action for display, e.g. `LEFT / A`, or `-` if none are bound.
+    pub fn describe(&self, action: Action) -> String {
+        let mut text = String::new();
+        for key in self.keys(action).into_iter().flatten() {
+            if !text.is_empty() {
+                text.push_str(" / ");
+            }
+            let _ = write!(text, "{key}");
+        }
+        if text.is_empty() {
+            text.push('-');
+        }
+        text
+    }
+
+    /// The body of the `[controls]` section of `ruefi.cfg`: one `action = key, key` line per
+    /// action, with `none` for unbound actions.
+    pub fn to_config(&self) -> String {
+        let mut text = String::new();
+        for action in Action::ALL {
+            let names: Vec<String> = self
+                .keys(action)
+                .into_iter()
+                .flatten()
+                .map(|k| format!("{k}").to_ascii_lowercase())
+                .collect();
+            let value = if names.is_empty() {
+                String::from("none")
+            } else {
+                names.join(", ")
+            };
+            let _ = writeln!(text, "{} = {value}", action.name());
+        }
+        text
+    }
+}
+
+/// Rows of the controls screen below the actions.
+const PRESET_ARROWS_ROW: usize = Action::ALL.len();
+const PRESET_WASD_ROW: usize = PRESET_ARROWS_ROW + 1;
+const SAVE_ROW: usize = PRESET_WASD_ROW + 1;
+
+/// What the controls screen asks the game to do.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ControlsCommand {
+    /// Write the bindings to the configuration file.
+    Save,
+    /// Leave the screen.
+    Close,
+}
+
+/// State of the controls screen: a list of actions followed by presets and a save entry.
+/// Activating an action waits for the key to bind to it.
+#[derive(Clone, Debug, Default)]
+pub struct ControlsMenu {
+    pub selected: usize,
+    pub status: Option<&'static str>, // result of the last operation, shown in the footer
+    capturing: bool,                  // waiting for a key to bind to the selected action
+}
+
+impl ControlsMenu {
+    /// Number of rows.
+    pub const LEN: usize = SAVE_ROW + 1;
+
+    /// Row labels for the current bindings.
+    pub fn items(&self, bindings: &Bindings) -> Vec<String> {
+        let mut items: Vec<String> = Action::ALL
+            .into_iter()
+            .enumerate()
+            .map(|(i, action)| {
+                if self.capturing && i == self.selected {
+                    format!("{}: PRESS A KEY", action.label())
+                } else {
+                    format!("{}: {}", action.label(), bindings.describe(action))
+                }
+            })
+            .collect();
+        items.push(String::from("USE ARROW KEYS"));
+        items.push(String::from("USE WASD"));
+        items.push(String::from("SAVE"));
+        items
+    }
+
+    /// Key hints, or the result of the last operation.
+    pub fn footer(&self) -> &'static str {
+        match (self.capturing, self.status) {
+            (true, _) => "PRESS THE NEW KEY  ESC CANCEL",
+            (false, Some(status)) => status,
+            (false, None) => "ENTER REBIND  DEL CLEAR  ESC BACK",
+        }
+    }
+
+    /// Whether the screen waits for a key to bind.
+    #[inline]
+    pub fn is_capturing(&self) -> bool {
+        self.capturing
+    }
+
+    /// Handle a key event. Only a first press is bound, so that the repeats of the key that
+    /// started the capture are not taken as the new key.
+    pub fn key(
+        &mut self,
+        key: KeyId,
+        first_press: bool,
+        bindings: &mut Bindings,
+    ) -> Option<ControlsCommand> {
+        if self.capturing {
+            if !first_press {
+                return None;
+            }
+            self.capturing = false;
+            if key != KeyId::Scan(SCAN_ESCAPE) {
+                let action = Action::ALL[self.selected];
+                if !bindings.bind(action, key) {
+                    self.status = Some("THAT KEY IS RESERVED");
+                }
+            }
+            return None;
+        }
+        match key {
+            KeyId::Scan(SCAN_UP) => self.selected = self.selected.saturating_sub(1),
+            KeyId::Scan(SCAN_DOWN) => self.selected = (self.selected + 1).min(Self::LEN - 1),
+            KeyId::Char('\r') if first_press => return self.activate(bindings),
+            KeyId::Scan(SCAN_DELETE) | KeyId::Char('\u{8}') => {
+                if let Some(&action) = Action::ALL.get(self.selected) {
+                    bindings.clear(action);
+                }
+            }
+            KeyId::Scan(SCAN_ESCAPE) => return Some(ControlsCommand::Close),
+            _ => {}
+        }
+        None
+    }
+
+    /// Activate the selected row, e.g. on Enter or a click.
+    pub fn activate(&mut self, bindings: &mut Bindings) -> Option<ControlsCommand> {
+        self.status = None;
+        match self.selected {
+            PRESET_ARROWS_ROW => *bindings = Bindings::preset(Preset::Arrows),
+            PRESET_WASD_ROW => *bindings = Bindings::preset(Preset::Wasd),
+            SAVE_ROW => return Some(ControlsCommand::Save),
+            _ => self.capturing = true,
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn binding_a_key_moves_it_from_other_actions() {
+        let mut bindings = Bindings::default();
+        assert!(bindings.bind(Action::Fire, KeyId::Scan(SCAN_UP)));
+        assert_eq!(
+            bindings.keys(Action::Fire),
+            [Some(KeyId::Scan(SCAN_UP)), Some(KeyId::Char(' '))]
+        );
+        assert_eq!(bindings.keys(Action::Thrust), [None, None]);
+        assert_eq!(bindings.action(KeyId::Scan(SCAN_UP)), Some(Action::Fire));
+        assert!(!bindings.bind(Action::Fire, KeyId::Scan(SCAN_ESCAPE)));
+    }
+
+    #[test]
+    fn wasd_preset_keeps_the_other_actions() {
+        let wasd = Bindings::preset(Preset::Wasd);
+        assert_eq!(wasd.action(KeyId::Char('w')), Some(Action::Thrust));
+        assert_eq!(wasd.action(KeyId::Scan(SCAN_UP)), None);
+        assert_eq!(
+            wasd.keys(Action::Fire),
+            Bindings::default().keys(Action::Fire)
+        );
+        for action in Action::ALL {
+            assert!(!wasd.any_key(action, |k| RESERVED_KEYS.contains(&k)));
+        }
+    }
+
+    #[test]
+    fn menu_rebinds_the_selected_action() {
+        let mut bindings = Bindings::default();
+        let mut menu = ControlsMenu::default();
+        menu.key(KeyId::Scan(SCAN_DOWN), true, &mut bindings);
+        menu.key(KeyId::Char('\r'), true, &mut bindings);
+        assert!(menu.is_capturing());
+        menu.key(KeyId::Char('\r'), false, &mut bindings);
+        assert_eq!(menu.items(&bindings)[1], "ROTATE RIGHT: PRESS A KEY");
+        menu.key(KeyId::char('X'), true, &mut bindings);
+        assert_eq!(menu.items(&bindings)[1], "ROTATE RIGHT: X / RIGHT");
+
+        // Reserved keys are refused, Escape cancels and then leaves the screen
+        menu.key(KeyId::Char('\r'), true, &mut bindings);
+        menu.key(KeyId::Char('v'), true, &mut bindings);
+        assert_eq!(menu.footer(), "THAT KEY IS RESERVED");
+        menu.key(KeyId::Char('\r'), true, &mut bindings);
+        assert_eq!(
+            menu.key(KeyId::Scan(SCAN_ESCAPE), true, &mut bindings),
+            None
+        );
+        assert_eq!(
+            menu.key(KeyId::Scan(SCAN_ESCAPE), true, &mut bindings),
+            Some(ControlsCommand::Close)
+        );
+        assert_eq!(bindings.describe(Action::RotateRight), "X / RIGHT");
+    }
+
+    #[test]
+    fn config_lines_list_every_action() {
+        let mut bindings = Bindings::default();
+        bindings.clear(Action::Pause);
+        bindings.bind(Action::Fire, KeyId::Char(','));
+        let text = bindings.to_config();
+        assert!(text.starts_with("rotate_left = left\n"));
+        assert!(text.contains("fire = comma, space\n"));
+        assert!(text.contains("pause = none\n"));
+        assert_eq!(text.lines().count(), Action::ALL.len());
+    }
+}
@@ -0,0 +1,233 @@
+use alloc::vec::Vec;
+
+extern crate alloc;
+
+/// Number of entries kept in the high score table.
+pub const MAX_ENTRIES: usize = 10;
+
+/// Number of letters in a player's initials.
+pub const INITIALS_LEN: usize = 3;
+
+/// Serialization header: magic bytes and format version.
+const MAGIC: [u8; 4] = *b"RHSC";
+const VERSION: u8 = 1;
+
+/// Serialized size of the header (magic, version, entry count), a single entry and the checksum.
+const HEADER_LEN: usize = MAGIC.len() + 2;
+const ENTRY_LEN: usize = 12;
+const CHECKSUM_LEN: usize = 4;
+
+/// A single high score table entry.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HighScore {
+    pub score: u32,
+    pub wave: u32,                    // wave reached when the game ended
+    pub initials: [u8; INITIALS_LEN], // uppercase ASCII letters
+}
+
+/// The top scores, sorted from best to worst.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HighScoreTable {
+    entries: Vec<HighScore>,
+}
+
+impl HighScoreTable {
+    #[inline]
+    pub fn entries(&self) -> &[HighScore] {
+        &self.entries
+    }
+
+    /// Whether a game with the given score earns a place in the table.
+    pub fn qualifies(&self, score: u32) -> bool {
+        score > 0
+            && (self.entries.len() < MAX_ENTRIES
+                || self.entries.last().is_some_and(|e| score > e.score))
+    }
+
+    /// Insert an entry and return its rank (0 = best), or `None` if it did not make the table.
+    /// Newer entries rank below older ones with the same score.
+    pub fn insert(&mut self, entry: HighScore) -> Option<usize> {
+        if !self.qualifies(entry.score) {
+            return None;
+        }
+        let rank = self.entries.partition_point(|e| e.score >= entry.score);
+        self.entries.insert(rank, entry);
+        self.entries.truncate(MAX_ENTRIES);
+        Some(rank)
+    }
+
+    /// Serialize the table: header, entries (little-endian score and wave, initials,
+    /// one padding byte) and an FNV-1a checksum over everything before it.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out =
+            Vec::with_capacity(HEADER_LEN + self.entries.len() * ENTRY_LEN + CHECKSUM_LEN);
+        out.extend_from_slice(&MAGIC);
+        out.push(VERSION);
+        out.push(self.entries.len() as u8);
+        for e in &self.entries {
+            out.extend_from_slice(&e.score.to_le_bytes());
+            out.extend_from_slice(&e.wave.to_le_bytes());
+            out.extend_from_slice(&e.initials);
+            out.push(0);
+        }
+        let checksum = fnv1a(&out);
+        out.extend_from_slice(&checksum.to_le_bytes());
+        out
+    }
+
+    /// Deserialize a table written by [`Self::to_bytes`]; `None` if the data is corrupt.
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        let (body, checksum) = data.split_last_chunk::<CHECKSUM_LEN>()?;
+        if fnv1a(body) != u32::from_le_bytes(*checksum) {
+            return None;
+        }
+        let (header, entries) = body.split_first_chunk::<HEADER_LEN>()?;
+        let count = header[5] as usize;
+        if header[..4] != MAGIC
+            || header[4] != VERSION
+            || count > MAX_ENTRIES
+            || entries.len() != count * ENTRY_LEN
+        {
+            return None;
+        }
+
+        let mut table = Self::default();
+        for chunk in entries.chunks_exact(ENTRY_LEN) {
+            let initials = [chunk[8], chunk[9], chunk[10]];
+            if !initials.iter().all(u8::is_ascii_uppercase) {
+                return None;
+            }
+            table.entries.push(HighScore {
+                score: u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]),
+                wave: u32::from_le_bytes([chunk[4], chunk[5], chunk[6], chunk[7]]),
+                initials,
+            });
+        }
+        if !table.entries.is_sorted_by(|a, b| a.score >= b.score) {
+            return None;
+        }
+        Some(table)
+    }
+}
+
+/// 32-bit FNV-1a hash, used to detect corrupted or foreign variable contents.
+fn fnv1a(data: &[u8]) -> u32 {
+    data.iter().fold(0x811C_9DC5, |hash, &b| {
+        (hash ^ b as u32).wrapping_mul(0x0100_0193)
+    })
+}
+
+/// Editing state of the initials-entry screen: three letters and a cursor.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InitialsEntry {
+    pub initials: [u8; INITIALS_LEN],
+    pub cursor: usize,
+}
+
+impl Default for InitialsEntry {
+    fn default() -> Self {
+        Self {
+            initials: [b'A'; INITIALS_LEN],
+            cursor: 0,
+        }
+    }
+}
+
+impl InitialsEntry {
+    /// Step the letter under the cursor through the alphabet, wrapping around.
+    pub fn cycle(&mut self, delta: i8) {
+        let letter = &mut self.initials[self.cursor];
+        let index = (*letter - b'A') as i16 + delta as i16;
+        *letter = b'A' + index.rem_euclid(26) as u8;
+    }
+
+    /// Move the cursor left or right, staying within the initials.
+    pub fn move_cursor(&mut self, delta: i8) {
+        self.cursor = self
+            .cursor
+            .saturating_add_signed(delta as isize)
+            .min(INITIALS_LEN - 1);
+    }
+
+    /// Set the letter under the cursor from a typed character and advance;
+    /// returns `false` if the character is not a letter.
+    pub fn type_char(&mut self, c: char) -> bool {
+        if !c.is_ascii_alphabetic() {
+            return false;
+        }
+        self.initials[self.cursor] = c.to_ascii_uppercase() as u8;
+        self.move_cursor(1);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(score: u32, initials: &[u8; 3]) -> HighScore {
+        HighScore {
+            score,
+            wave: 1,
+            initials: *initials,
+        }
+    }
+
+    #[test]
+    fn insert_keeps_table_sorted_and_bounded() {
+        let mut table = HighScoreTable::default();
+        for i in 1..=12 {
+            table.insert(entry(i * 100, b"AAA"));
+        }
+        assert_eq!(table.entries().len(), MAX_ENTRIES);
+        assert_eq!(table.entries()[0].score, 1200);
+        assert_eq!(table.entries()[MAX_ENTRIES - 1].score, 300);
+
+        assert!(!table.qualifies(300));
+        assert_eq!(table.insert(entry(250, b"BBB")), None);
+        assert_eq!(table.insert(entry(1200, b"CCC")), Some(1));
+        assert_eq!(table.entries()[MAX_ENTRIES - 1].score, 400);
+    }
+
+    #[test]
+    fn zero_scores_do_not_qualify() {
+        assert!(!HighScoreTable::default().qualifies(0));
+    }
+
+    #[test]
+    fn serialization_round_trips() {
+        let mut table = HighScoreTable::default();
+        table.insert(entry(1500, b"ABC"));
+        table.insert(entry(700, b"XYZ"));
+        let bytes = table.to_bytes();
+        assert_eq!(HighScoreTable::from_bytes(&bytes), Some(table));
+    }
+
+    #[test]
+    fn corrupt_data_is_rejected() {
+        let mut table = HighScoreTable::default();
+        table.insert(entry(1500, b"ABC"));
+        let bytes = table.to_bytes();
+
+        let mut flipped = bytes.clone();
+        flipped[7] ^= 1;
+        assert!(HighScoreTable::from_bytes(&flipped).is_none());
+        assert!(HighScoreTable::from_bytes(&bytes[..bytes.len() - 1]).is_none());
+        assert!(HighScoreTable::from_bytes(&[]).is_none());
+    }
+
+    #[test]
+    fn initials_entry_editing() {
+        let mut entry = InitialsEntry::default();
+        entry.cycle(-1);
+        assert_eq!(entry.initials, *b"ZAA");
+        assert!(entry.type_char('q'));
+        assert!(!entry.type_char('1'));
+        entry.cycle(2);
+        assert_eq!(entry.initials, *b"QCA");
+        entry.move_cursor(5);
+        assert_eq!(entry.cursor, INITIALS_LEN - 1);
+        entry.move_cursor(-5);
+        assert_eq!(entry.cursor, 0);
+    }
+}
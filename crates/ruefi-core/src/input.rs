@@ -0,0 +1,411 @@
+//! Held-key tracking on top of firmware key events.
+//!
+//! UEFI only reports key presses (repeated while a key is held), never releases. A key
+//! therefore counts as held from its first event until no further event arrives within a
+//! timeout. The timeout starts long enough to bridge the firmware's initial repeat delay and
+//! then adapts to the observed repeat interval, so releases are detected promptly at any
+//! repeat rate.
+//!
+//! Firmware typically repeats only the most recently pressed key. Keys whose repeats stopped
+//! because a newer key was pressed therefore stay held until that key is released, and are
+//! released on their own timeout afterwards, whether or not they are still physically down.
+
+use crate::controls::{Action, Bindings};
+use core::fmt;
+
+/// Keys tracked at the same time; further keys are ignored until one is released.
+const MAX_HELD_KEYS: usize = 8;
+
+/// Seconds a key counts as held after its first event, bridging the initial repeat delay.
+const INITIAL_HOLD: f32 = 0.6;
+
+/// Bounds of the hold timeout once repeats arrive, in seconds.
+const MIN_HOLD: f32 = 0.05;
+const MAX_HOLD: f32 = 0.3;
+
+/// Hold timeout as a multiple of the measured repeat interval.
+const REPEAT_SLACK: f32 = 2.5;
+
+/// Scan codes of special keys, as defined by the UEFI specification.
+pub const SCAN_UP: u16 = 0x01;
+pub const SCAN_DOWN: u16 = 0x02;
+pub const SCAN_RIGHT: u16 = 0x03;
+pub const SCAN_LEFT: u16 = 0x04;
+pub const SCAN_DELETE: u16 = 0x08;
+pub const SCAN_ESCAPE: u16 = 0x17;
+
+/// Scan codes of F1 to F12, which are consecutive.
+const SCAN_F1: u16 = 0x0B;
+const SCAN_F12: u16 = 0x16;
+
+/// Names of the other special keys in key bindings.
+const SCAN_NAMES: [(u16, &str); 11] = [
+    (SCAN_UP, "UP"),
+    (SCAN_DOWN, "DOWN"),
+    (SCAN_RIGHT, "RIGHT"),
+    (SCAN_LEFT, "LEFT"),
+    (0x05, "HOME"),
+    (0x06, "END"),
+    (0x07, "INSERT"),
+    (SCAN_DELETE, "DELETE"),
+    (0x09, "PAGEUP"),
+    (0x0A, "PAGEDOWN"),
+    (SCAN_ESCAPE, "ESC"),
+];
+
+/// Names of characters that are invisible or have a meaning in `ruefi.cfg`.
+const CHAR_NAMES: [(char, &str); 6] = [
+    (' ', "SPACE"),
+    ('\t', "TAB"),
+    ('\r', "ENTER"),
+    ('\u{8}', "BACKSPACE"),
+    ('#', "HASH"),
+    (',', "COMMA"),
+];
+
+/// A physical key as reported by the firmware: a scan code for special keys, otherwise the
+/// character it produces (lowercase, so that Shift does not make it a different key).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyId {
+    Scan(u16),
+    Char(char),
+}
+
+impl KeyId {
+    /// Identify a character key, ignoring case.
+    pub fn char(c: char) -> Self {
+        KeyId::Char(c.to_ascii_lowercase())
+    }
+
+    /// Parse a key name as written by [`Display`](fmt::Display), ignoring case: `LEFT`, `F5`,
+    /// `SPACE`, a single character such as `w`, or `SCAN_xx` with a hexadecimal scan code.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let mut chars = name.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return Some(KeyId::char(c));
+        }
+        let name = name.to_ascii_uppercase();
+        if let Some(&(c, _)) = CHAR_NAMES.iter().find(|(_, n)| *n == name) {
+            return Some(KeyId::Char(c));
+        }
+        if let Some(&(code, _)) = SCAN_NAMES.iter().find(|(_, n)| *n == name) {
+            return Some(KeyId::Scan(code));
+        }
+        if let Some(n) = name.strip_prefix('F').and_then(|n| n.parse::<u16>().ok())
+            && (1..=SCAN_F12 - SCAN_F1 + 1).contains(&n)
+        {
+            return Some(KeyId::Scan(SCAN_F1 + n - 1));
+        }
+        let code = u16::from_str_radix(name.strip_prefix("SCAN_")?, 16).ok()?;
+        Some(KeyId::Scan(code))
+    }
+}
+
+impl fmt::Display for KeyId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            KeyId::Scan(code @ SCAN_F1..=SCAN_F12) => write!(f, "F{}", code - SCAN_F1 + 1),
+            KeyId::Scan(code) => match SCAN_NAMES.iter().find(|(c, _)| *c == code) {
+                Some((_, name)) => f.write_str(name),
+                None => write!(f, "SCAN_{code:02X}"),
+            },
+            KeyId::Char(c) => match CHAR_NAMES.iter().find(|(n, _)| *n == c) {
+                Some((_, name)) => f.write_str(name),
+                None => write!(f, "{}", c.to_ascii_uppercase()),
+            },
+        }
+    }
+}
+
+/// Modifier keys held during the most recent key event, if the firmware reports them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub ctrl: bool,
+    pub alt: bool,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct HeldKey {
+    id: KeyId,
+    since_event: f32, // seconds since the last event of this key
+    timeout: f32,     // seconds without events after which the key counts as released
+    fresh: bool,      // first event arrived since the last tick
+    repeated: bool,   // the firmware repeated the key, so it is certainly held down
+    order: u32,       // position in the order of first presses
+    superseded: bool, // a newer key was pressed since the last event of this key
+    ignored: bool,    // held since before the last `ignore_held`
+}
+
+/// The set of keys currently considered held.
+#[derive(Clone, Debug, Default)]
+pub struct HeldKeys {
+    keys: [Option<HeldKey>; MAX_HELD_KEYS],
+    presses: u32, // first presses so far, for ordering the held keys
+    pub modifiers: Modifiers,
+}
+
+impl HeldKeys {
+    /// Record a key event, either the initial press or a repeat.
+    pub fn press(&mut self, id: KeyId, modifiers: Modifiers) {
+        self.modifiers = modifiers;
+        if let Some(key) = self.keys.iter_mut().flatten().find(|k| k.id == id) {
+            key.timeout = (key.since_event * REPEAT_SLACK).clamp(MIN_HOLD, MAX_HOLD);
+            key.since_event = 0.0;
+            key.repeated = true;
+            key.superseded = false;
+        } else if let Some(free) = self.keys.iter().position(Option::is_none) {
+            // The firmware stops repeating the other keys in favor of this one
+            for key in self.keys.iter_mut().flatten() {
+                key.superseded = true;
+            }
+            self.presses = self.presses.wrapping_add(1);
+            self.keys[free] = Some(HeldKey {
+                id,
+                since_event: 0.0,
+                timeout: INITIAL_HOLD,
+                fresh: true,
+                repeated: false,
+                order: self.presses,
+                superseded: false,
+                ignored: false,
+            });
+        }
+    }
+
+    /// Advance time by `dt` seconds, releasing keys whose timeout expired. The timeout of
+    /// a superseded key only runs once the newest key is released.
+    pub fn tick(&mut self, dt: f32) {
+        let newest = self.keys.iter().flatten().map(|k| k.order).max();
+        for slot in &mut self.keys {
+            if let Some(key) = slot {
+                if !key.superseded || Some(key.order) == newest {
+                    key.since_event += dt;
+                }
+                key.fresh = false;
+                if key.since_event > key.timeout {
+                    *slot = None;
+                }
+            }
+        }
+        if self.keys.iter().all(Option::is_none) {
+            self.modifiers = Modifiers::default();
+        }
+    }
+
+    /// Release all keys, e.g. when a menu takes over the keyboard.
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Ignore the keys held now until they are released, e.g. so that gameplay keys still
+    /// held when the game ends do not type into the initials entry.
+    pub fn ignore_held(&mut self) {
+        for key in self.keys.iter_mut().flatten() {
+            key.ignored = true;
+        }
+    }
+
+    /// Whether the key has been held since the last [`ignore_held`](Self::ignore_held).
+    #[inline]
+    pub fn is_ignored(&self, id: KeyId) -> bool {
+        self.find(id).is_some_and(|k| k.ignored)
+    }
+
+    #[inline]
+    pub fn is_held(&self, id: KeyId) -> bool {
+        self.find(id).is_some()
+    }
+
+    /// Whether the key was first pressed since the last [`tick`](Self::tick).
+    #[inline]
+    pub fn just_pressed(&self, id: KeyId) -> bool {
+        self.find(id).is_some_and(|k| k.fresh)
+    }
+
+    /// Whether the key is held past the firmware's repeat delay. A single tap counts as held
+    /// until the delay passes, but never as repeating.
+    #[inline]
+    pub fn is_repeating(&self, id: KeyId) -> bool {
+        self.find(id).is_some_and(|k| k.repeated)
+    }
+
+    fn find(&self, id: KeyId) -> Option<&HeldKey> {
+        self.keys.iter().flatten().find(|k| k.id == id)
+    }
+}
+
+/// What the player asks the ship to do, derived from the held keys once per frame
+/// and applied to every simulation step of that frame.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct InputState {
+    pub rotate: i8, // -1 counterclockwise, 0, 1 clockwise
+    pub thrust: i8, // -1 reverse, 0, 1 forward
+    pub fire: bool, // fire key just pressed, or held down for auto-fire
+    pub modifiers: Modifiers,
+}
+
+impl InputState {
+    /// Map the held keys to ship controls through the key bindings.
+    /// Call before [`HeldKeys::tick`], which ends the "just pressed" state.
+    pub fn from_keys(keys: &HeldKeys, bindings: &Bindings) -> Self {
+        let held = |action| bindings.any_key(action, |k| keys.is_held(k));
+        let axis = |neg, pos| held(pos) as i8 - held(neg) as i8;
+        let fire = bindings.any_key(Action::Fire, |k| {
+            keys.just_pressed(k) || keys.is_repeating(k)
+        });
+        Self {
+            rotate: axis(Action::RotateLeft, Action::RotateRight),
+            thrust: axis(Action::Reverse, Action::Thrust),
+            fire,
+            modifiers: keys.modifiers,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DT: f32 = 1.0 / 60.0;
+    const UP: KeyId = KeyId::Scan(SCAN_UP);
+    const FIRE: KeyId = KeyId::Char(' ');
+
+    /// Simulate holding a key: a first event, then repeats every `interval` after `delay`.
+    fn hold(keys: &mut HeldKeys, delay: f32, interval: f32, duration: f32) -> bool {
+        let mut held_throughout = true;
+        let (mut t, mut next) = (0.0, 0.0);
+        while t < duration {
+            if t >= next {
+                keys.press(UP, Modifiers::default());
+                next = if next == 0.0 { delay } else { next + interval };
+            }
+            held_throughout &= keys.is_held(UP);
+            keys.tick(DT);
+            t += DT;
+        }
+        held_throughout
+    }
+
+    #[test]
+    fn keys_stay_held_at_any_repeat_rate() {
+        for (delay, interval) in [(0.5, 1.0 / 30.0), (0.25, 0.1), (0.5, 0.2)] {
+            let mut keys = HeldKeys::default();
+            assert!(hold(&mut keys, delay, interval, 2.0), "{delay} {interval}");
+        }
+    }
+
+    #[test]
+    fn keys_are_released_soon_after_repeats_stop() {
+        let mut keys = HeldKeys::default();
+        hold(&mut keys, 0.5, 1.0 / 30.0, 1.0);
+        let mut released_after = 0.0;
+        while keys.is_held(UP) {
+            keys.tick(DT);
+            released_after += DT;
+        }
+        assert!(released_after < 0.15, "{released_after}");
+    }
+
+    #[test]
+    fn older_keys_stay_held_while_a_newer_key_repeats() {
+        // The firmware only repeats the most recent key: pressing Left ends the repeats of Up
+        const LEFT: KeyId = KeyId::Scan(SCAN_LEFT);
+        let mut keys = HeldKeys::default();
+        let (mut t, mut start, mut next, mut key) = (0.0, 0.0, 0.0, UP);
+        while t < 1.5 {
+            if key == UP && t >= 0.5 {
+                (start, next, key) = (t, t, LEFT);
+            }
+            if t >= next {
+                keys.press(key, Modifiers::default());
+                next = if next == start {
+                    start + 0.5
+                } else {
+                    next + 1.0 / 30.0
+                };
+            }
+            assert!(keys.is_held(UP), "{t}");
+            assert_eq!(keys.is_held(LEFT), key == LEFT, "{t}");
+            keys.tick(DT);
+            t += DT;
+        }
+
+        // Once Left is released, Up follows on its own timeout
+        let mut released_after = 0.0;
+        while keys.is_held(UP) {
+            assert!(!keys.is_held(LEFT) || released_after < 0.15);
+            keys.tick(DT);
+            released_after += DT;
+        }
+        assert!(released_after < 0.3, "{released_after}");
+    }
+
+    #[test]
+    fn single_press_is_held_briefly() {
+        let mut keys = HeldKeys::default();
+        keys.press(UP, Modifiers::default());
+        keys.tick(0.3);
+        assert!(keys.is_held(UP));
+        keys.tick(0.4);
+        assert!(!keys.is_held(UP));
+    }
+
+    #[test]
+    fn ignored_keys_count_again_once_released() {
+        let mut keys = HeldKeys::default();
+        keys.press(UP, Modifiers::default());
+        keys.tick(DT);
+        keys.ignore_held();
+        keys.press(UP, Modifiers::default());
+        keys.press(FIRE, Modifiers::default());
+        assert!(keys.is_ignored(UP));
+        assert!(!keys.is_ignored(FIRE));
+        while keys.is_held(UP) {
+            keys.tick(DT);
+        }
+        keys.press(UP, Modifiers::default());
+        assert!(!keys.is_ignored(UP));
+    }
+
+    #[test]
+    fn simultaneous_keys_combine_into_input_state() {
+        let mut keys = HeldKeys::default();
+        let shift = Modifiers {
+            shift: true,
+            ..Modifiers::default()
+        };
+        keys.press(KeyId::Scan(SCAN_LEFT), Modifiers::default());
+        keys.press(UP, Modifiers::default());
+        keys.press(KeyId::char(' '), shift);
+        let input = InputState::from_keys(&keys, &Bindings::default());
+        assert_eq!((input.rotate, input.thrust, input.fire), (-1, 1, true));
+        assert!(input.modifiers.shift);
+    }
+
+    #[test]
+    fn a_tap_fires_once_and_holding_fires_after_the_repeat_delay() {
+        let mut keys = HeldKeys::default();
+        keys.press(FIRE, Modifiers::default());
+        assert!(InputState::from_keys(&keys, &Bindings::default()).fire);
+        keys.tick(DT);
+        assert!(keys.is_held(FIRE));
+        assert!(!InputState::from_keys(&keys, &Bindings::default()).fire);
+        keys.tick(0.5);
+        keys.press(FIRE, Modifiers::default());
+        keys.tick(DT);
+        assert!(InputState::from_keys(&keys, &Bindings::default()).fire);
+    }
+
+    #[test]
+    fn key_names_round_trip() {
+        for name in ["LEFT", "F5", "F12", "SPACE", "W", "[", "HASH", "SCAN_48"] {
+            let key = KeyId::from_name(name).unwrap();
+            assert_eq!(alloc::format!("{key}"), name);
+        }
+        assert_eq!(KeyId::from_name("pageDown"), Some(KeyId::Scan(0x0A)));
+        assert_eq!(KeyId::from_name("W"), Some(KeyId::Char('w')));
+        assert_eq!(KeyId::from_name("F13"), None);
+        assert_eq!(KeyId::from_name("warp"), None);
+    }
+}
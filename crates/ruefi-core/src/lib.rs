@@ -0,0 +1,22 @@
+//! Platform-independent game simulation for Rüfi.
+//!
+//! Everything in here is `no_std` and free of UEFI dependencies so that it can be
+//! unit tested on the host with `cargo test`.
+
+#![cfg_attr(not(test), no_std)]
+
+extern crate alloc;
+
+pub mod asteroid;
+pub mod config;
+pub mod controls;
+pub mod highscore;
+pub mod input;
+pub mod particle;
+pub mod pointer;
+pub mod projectile;
+pub mod rand;
+pub mod score;
+pub mod ship;
+pub mod state;
+pub mod wave;
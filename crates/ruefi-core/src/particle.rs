@@ -0,0 +1,242 @@
+use crate::rand::XorShift64;
+use crate::ship::Ship;
+use alloc::vec::Vec;
+use libm::{cosf, sinf};
+
+/// Maximum number of particles alive at the same time; new particles replace existing ones beyond that.
+pub const PARTICLE_CAPACITY: usize = 1024;
+
+/// Debris particles per pixel of radius of a destroyed asteroid.
+const DEBRIS_PER_RADIUS: f32 = 0.6;
+
+/// Particles in the burst of a destroyed ship.
+const SHIP_BURST_COUNT: usize = 80;
+
+/// Exhaust particles emitted per thrusting simulation step.
+const EXHAUST_PER_STEP: usize = 2;
+
+/// A short-lived point that moves in a straight line and fades out.
+#[derive(Clone, Copy, Debug)]
+pub struct Particle {
+    pub x: f32,
+    pub y: f32,
+    pub vx: f32,             // pixels/second
+    pub vy: f32,             // pixels/second
+    pub age: f32,            // seconds since emission
+    pub lifetime: f32,       // seconds until the particle disappears
+    pub color: (u8, u8, u8), // color at emission, fading to black over the lifetime
+}
+
+impl Particle {
+    /// Remaining brightness, from 1 at emission down to 0 at the end of the lifetime.
+    #[inline]
+    pub fn fade(&self) -> f32 {
+        (1.0 - self.age / self.lifetime).clamp(0.0, 1.0)
+    }
+}
+
+/// Fixed-capacity pool of particles; the storage is allocated once and never grows.
+pub struct ParticlePool {
+    particles: Vec<Particle>,
+    capacity: usize,
+    next: usize, // slot replaced next when the pool is full
+}
+
+impl Default for ParticlePool {
+    fn default() -> Self {
+        Self::new(PARTICLE_CAPACITY)
+    }
+}
+
+impl ParticlePool {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            particles: Vec::with_capacity(capacity),
+            capacity,
+            next: 0,
+        }
+    }
+
+    /// The particles currently alive.
+    #[inline]
+    pub fn particles(&self) -> &[Particle] {
+        &self.particles
+    }
+
+    /// Remove all particles, e.g. when the screen is resized.
+    pub fn clear(&mut self) {
+        self.particles.clear();
+        self.next = 0;
+    }
+
+    /// Add a particle, replacing an old one if the pool is full.
+    pub fn emit(&mut self, particle: Particle) {
+        if self.particles.len() < self.capacity {
+            self.particles.push(particle);
+        } else if self.capacity > 0 {
+            self.next %= self.capacity;
+            self.particles[self.next] = particle;
+            self.next += 1;
+        }
+    }
+
+    /// Emit `count` particles from `at` in random directions, with speeds within
+    /// `speed` and lifetimes within `lifetime`.
+    pub fn burst(
+        &mut self,
+        at: (f32, f32),
+        count: usize,
+        speed: (f32, f32),
+        lifetime: (f32, f32),
+        color: (u8, u8, u8),
+        rng: &mut XorShift64,
+    ) {
+        for _ in 0..count {
+            let dir = rng.range_f32(0.0, core::f32::consts::TAU);
+            let v = rng.range_f32(speed.0, speed.1);
+            self.emit(Particle {
+                x: at.0,
+                y: at.1,
+                vx: cosf(dir) * v,
+                vy: sinf(dir) * v,
+                age: 0.0,
+                lifetime: rng.range_f32(lifetime.0, lifetime.1),
+                color,
+            });
+        }
+    }
+
+    /// Debris of an asteroid of the given radius destroyed at (`x`, `y`).
+    pub fn asteroid_debris(&mut self, x: f32, y: f32, radius: f32, rng: &mut XorShift64) {
+        let count = (radius * DEBRIS_PER_RADIUS) as usize + 4;
+        self.burst(
+            (x, y),
+            count,
+            (30.0, 160.0),
+            (0.3, 0.9),
+            (200, 200, 200),
+            rng,
+        );
+    }
+
+    /// Explosion of the player ship.
+    pub fn ship_explosion(&mut self, ship: &Ship, rng: &mut XorShift64) {
+        let at = (ship.x, ship.y);
+        self.burst(
+            at,
+            SHIP_BURST_COUNT / 2,
+            (40.0, 220.0),
+            (0.6, 1.6),
+            (255, 200, 80),
+            rng,
+        );
+        self.burst(
+            at,
+            SHIP_BURST_COUNT / 2,
+            (20.0, 120.0),
+            (0.4, 1.2),
+            (92, 127, 255),
+            rng,
+        );
+    }
+
+    /// Exhaust behind a thrusting ship, moving backwards (relative to the ship) within a narrow cone.
+    pub fn exhaust(&mut self, ship: &Ship, rng: &mut XorShift64) {
+        let (fx, fy) = ship.forward_vec();
+        let tail_x = ship.x - fx * ship.tri_h * 0.5;
+        let tail_y = ship.y - fy * ship.tri_h * 0.5;
+        for _ in 0..EXHAUST_PER_STEP {
+            // Rotate the backwards direction by a small random angle
+            let spread = rng.range_f32(-0.35, 0.35);
+            let (cs, sn) = (cosf(spread), sinf(spread));
+            let (dx, dy) = (-(fx * cs - fy * sn), -(fx * sn + fy * cs));
+            let v = rng.range_f32(80.0, 180.0);
+            self.emit(Particle {
+                x: tail_x,
+                y: tail_y,
+                vx: ship.vx + dx * v,
+                vy: ship.vy + dy * v,
+                age: 0.0,
+                lifetime: rng.range_f32(0.15, 0.4),
+                color: (255, 160, 40),
+            });
+        }
+    }
+
+    /// Age and move all particles over `dt` seconds, wrapping at the screen edges,
+    /// and drop those past their lifetime.
+    pub fn update(&mut self, dt: f32, sw: f32, sh: f32) {
+        for p in &mut self.particles {
+            p.age += dt;
+            p.x += p.vx * dt;
+            p.y += p.vy * dt;
+            if p.x < 0.0 {
+                p.x += sw;
+            } else if p.x >= sw {
+                p.x -= sw;
+            }
+            if p.y < 0.0 {
+                p.y += sh;
+            } else if p.y >= sh {
+                p.y -= sh;
+            }
+        }
+        self.particles.retain(|p| p.age < p.lifetime);
+        self.next = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dot(lifetime: f32) -> Particle {
+        Particle {
+            x: 10.0,
+            y: 10.0,
+            vx: 100.0,
+            vy: 0.0,
+            age: 0.0,
+            lifetime,
+            color: (200, 100, 50),
+        }
+    }
+
+    #[test]
+    fn particles_move_fade_and_expire() {
+        let mut pool = ParticlePool::new(8);
+        pool.emit(dot(1.0));
+        pool.emit(dot(0.2));
+        pool.update(0.5, 800.0, 600.0);
+        assert_eq!(pool.particles().len(), 1);
+        let p = pool.particles()[0];
+        assert_eq!(p.x, 60.0);
+        assert_eq!(p.fade(), 0.5);
+    }
+
+    #[test]
+    fn pool_never_grows_past_capacity() {
+        let mut pool = ParticlePool::new(4);
+        let mut rng = XorShift64::new(7);
+        pool.burst(
+            (0.0, 0.0),
+            10,
+            (10.0, 20.0),
+            (1.0, 2.0),
+            (255, 255, 255),
+            &mut rng,
+        );
+        assert_eq!(pool.particles().len(), 4);
+    }
+
+    #[test]
+    fn exhaust_leaves_the_tail_backwards() {
+        let ship = Ship::new(800, 600);
+        let mut pool = ParticlePool::new(16);
+        pool.exhaust(&ship, &mut XorShift64::new(1));
+        let (fx, fy) = ship.forward_vec();
+        for p in pool.particles() {
+            assert!(p.vx * fx + p.vy * fy < 0.0);
+        }
+    }
+}
@@ -0,0 +1,158 @@
+//! Mouse pointer state, fed by relative (mouse) or absolute (tablet, touch) pointer devices.
+
+use crate::ship::Ship;
+use libm::atan2f;
+
+/// Seconds after the last pointer movement or click during which the pointer steers the ship,
+/// so that keyboard players are not overridden by a resting mouse.
+const POINTER_IDLE_TIMEOUT: f32 = 3.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Button {
+    Left = 0,
+    Right = 1,
+}
+
+/// The on-screen cursor and its buttons.
+#[derive(Clone, Debug)]
+pub struct Cursor {
+    pub x: f32,
+    pub y: f32,
+    buttons: [bool; 2], // currently held
+    clicked: [bool; 2], // pressed since the last tick
+    moved: bool,        // moved since the last tick
+    idle: f32,          // seconds since the last movement or button activity
+}
+
+impl Cursor {
+    /// A cursor in the screen center; inactive until the pointer is used.
+    pub fn new(sw: usize, sh: usize) -> Self {
+        Self {
+            x: sw as f32 * 0.5,
+            y: sh as f32 * 0.5,
+            buttons: [false; 2],
+            clicked: [false; 2],
+            moved: false,
+            idle: f32::INFINITY,
+        }
+    }
+
+    /// Move by a relative amount in pixels, staying on screen.
+    pub fn move_by(&mut self, dx: f32, dy: f32, sw: f32, sh: f32) {
+        self.move_to(self.x + dx, self.y + dy, sw, sh);
+    }
+
+    /// Move to an absolute screen position, staying on screen.
+    pub fn move_to(&mut self, x: f32, y: f32, sw: f32, sh: f32) {
+        let (x, y) = (x.clamp(0.0, sw - 1.0), y.clamp(0.0, sh - 1.0));
+        if x != self.x || y != self.y {
+            self.x = x;
+            self.y = y;
+            self.moved = true;
+            self.idle = 0.0;
+        }
+    }
+
+    /// Update the held buttons, recording new presses as clicks.
+    pub fn set_buttons(&mut self, buttons: [bool; 2]) {
+        for (i, &down) in buttons.iter().enumerate() {
+            self.clicked[i] |= down && !self.buttons[i];
+        }
+        if buttons != self.buttons {
+            self.idle = 0.0;
+        }
+        self.buttons = buttons;
+    }
+
+    /// Advance time by `dt` seconds, ending this frame's clicks and movement.
+    pub fn tick(&mut self, dt: f32) {
+        self.clicked = [false; 2];
+        self.moved = false;
+        self.idle = if self.buttons.contains(&true) {
+            0.0
+        } else {
+            self.idle + dt
+        };
+    }
+
+    /// Whether the pointer was used recently.
+    #[inline]
+    pub fn is_active(&self) -> bool {
+        self.idle < POINTER_IDLE_TIMEOUT
+    }
+
+    /// Whether the pointer moved since the last tick.
+    #[inline]
+    pub fn moved(&self) -> bool {
+        self.moved
+    }
+
+    #[inline]
+    pub fn is_down(&self, button: Button) -> bool {
+        self.buttons[button as usize]
+    }
+
+    /// Whether the button was pressed since the last tick.
+    #[inline]
+    pub fn clicked(&self, button: Button) -> bool {
+        self.clicked[button as usize]
+    }
+
+    /// Rotation intent that turns the ship's nose toward the cursor; 0 once it points
+    /// there within one rotation step of `dt` seconds.
+    pub fn steer(&self, ship: &Ship, dt: f32) -> i8 {
+        let (fx, fy) = ship.forward_vec();
+        let (dx, dy) = (self.x - ship.x, self.y - ship.y);
+        // Signed angle from the nose to the cursor; positive rotation increases it
+        let diff = atan2f(fx * dy - fy * dx, fx * dx + fy * dy);
+        if diff.abs() <= ship.rot_speed * dt {
+            0
+        } else {
+            diff.signum() as i8
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DT: f32 = 1.0 / 60.0;
+
+    #[test]
+    fn ship_turns_toward_the_cursor_and_settles() {
+        let mut ship = Ship::new(800, 600);
+        let mut cursor = Cursor::new(800, 600);
+        cursor.move_to(ship.x + 100.0, ship.y - 100.0, 800.0, 600.0);
+        for _ in 0..120 {
+            let rot = cursor.steer(&ship, DT);
+            ship.angle += rot as f32 * ship.rot_speed * DT;
+        }
+        let (fx, fy) = ship.forward_vec();
+        let (dx, dy) = (cursor.x - ship.x, cursor.y - ship.y);
+        let cos = (fx * dx + fy * dy) / libm::sqrtf(dx * dx + dy * dy);
+        assert!(cos > 0.99, "{cos}");
+        assert_eq!(cursor.steer(&ship, DT), 0);
+    }
+
+    #[test]
+    fn clicks_last_one_tick_and_the_cursor_idles_out() {
+        let mut cursor = Cursor::new(800, 600);
+        assert!(!cursor.is_active());
+        cursor.set_buttons([true, false]);
+        assert!(cursor.clicked(Button::Left) && cursor.is_active());
+        cursor.tick(DT);
+        assert!(!cursor.clicked(Button::Left) && cursor.is_down(Button::Left));
+        cursor.set_buttons([false, false]);
+        cursor.tick(POINTER_IDLE_TIMEOUT + DT);
+        assert!(!cursor.is_active());
+    }
+
+    #[test]
+    fn cursor_stays_on_screen() {
+        let mut cursor = Cursor::new(800, 600);
+        cursor.move_by(-1000.0, 1000.0, 800.0, 600.0);
+        assert_eq!((cursor.x, cursor.y), (0.0, 599.0));
+        assert!(cursor.moved());
+    }
+}
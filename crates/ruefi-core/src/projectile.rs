@@ -0,0 +1,64 @@
+use crate::ship::Ship;
+
+/// Simple projectile moving in a straight line
+pub struct Projectile {
+    pub x: f32,
+    pub y: f32,
+    pub vx: f32, // pixels/second
+    pub vy: f32, // pixels/second
+}
+
+impl Projectile {
+    /// Spawn a projectile from the ship's nose along its current forward vector, scaled by `speed`.
+    #[inline]
+    pub fn spawn_from_ship(ship: &Ship, speed: f32) -> Self {
+        let (nx, ny) = ship.nose();
+        let (fx, fy) = ship.forward_vec();
+        Projectile {
+            x: nx,
+            y: ny,
+            vx: fx * speed,
+            vy: fy * speed,
+        }
+    }
+
+    /// Advance the projectile by its velocity over `dt` seconds.
+    #[inline]
+    pub fn update(&mut self, dt: f32) {
+        self.x += self.vx * dt;
+        self.y += self.vy * dt;
+    }
+
+    /// Check if the projectile is still within the screen bounds.
+    #[inline]
+    pub const fn on_screen(&self, sw: f32, sh: f32) -> bool {
+        self.x >= 0.0 && self.x < sw && self.y >= 0.0 && self.y < sh
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spawns_at_nose_moving_forward() {
+        let ship = Ship::new(800, 600);
+        let p = Projectile::spawn_from_ship(&ship, 100.0);
+        assert_eq!((p.x, p.y), ship.nose());
+        let (fx, fy) = ship.forward_vec();
+        assert_eq!((p.vx, p.vy), (fx * 100.0, fy * 100.0));
+    }
+
+    #[test]
+    fn leaves_the_screen() {
+        let mut p = Projectile {
+            x: 790.0,
+            y: 300.0,
+            vx: 600.0,
+            vy: 0.0,
+        };
+        assert!(p.on_screen(800.0, 600.0));
+        p.update(0.1);
+        assert!(!p.on_screen(800.0, 600.0));
+    }
+}
@@ -0,0 +1,85 @@
+/// Simple xorshift RNG for no_std usage.
+/// Seeding is left to the caller, e.g. from a platform timing source.
+pub struct XorShift64 {
+    state: u64,
+}
+
+impl XorShift64 {
+    #[inline]
+    pub fn new(seed: u64) -> Self {
+        // Avoid zero state
+        let s = if seed == 0 { 0x9e3779b97f4a7c15 } else { seed };
+        Self { state: s }
+    }
+
+    #[inline]
+    pub fn next_u64(&mut self) -> u64 {
+        // xorshift64*
+        let mut x = self.state;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.state = x;
+        x.wrapping_mul(0x2545F4914F6CDD1D)
+    }
+
+    #[inline]
+    pub fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    #[inline]
+    pub fn next_f32(&mut self) -> f32 {
+        // Uniform in [0,1); use 24 bits so the value is exactly representable and never rounds up to 1.0
+        let v = self.next_u32() >> 8;
+        (v as f32) / ((1u32 << 24) as f32)
+    }
+
+    #[inline]
+    pub fn range_f32(&mut self, min: f32, max: f32) -> f32 {
+        min + self.next_f32() * (max - min)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_seed_is_replaced() {
+        let mut rng = XorShift64::new(0);
+        assert_ne!(rng.next_u64(), 0);
+    }
+
+    #[test]
+    fn same_seed_gives_same_sequence() {
+        let mut a = XorShift64::new(1234);
+        let mut b = XorShift64::new(1234);
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+    }
+
+    #[test]
+    fn next_f32_is_in_unit_interval() {
+        let mut rng = XorShift64::new(99);
+        for _ in 0..100_000 {
+            let v = rng.next_f32();
+            assert!((0.0..1.0).contains(&v), "{v}");
+        }
+    }
+
+    #[test]
+    fn range_f32_stays_in_range() {
+        let mut rng = XorShift64::new(5);
+        let (mut lo, mut hi) = (f32::MAX, f32::MIN);
+        for _ in 0..10_000 {
+            let v = rng.range_f32(-3.0, 7.5);
+            assert!((-3.0..7.5).contains(&v), "{v}");
+            lo = lo.min(v);
+            hi = hi.max(v);
+        }
+        // The samples should cover most of the range
+        assert!(lo < -2.9 && hi > 7.4);
+    }
+}
@@ -0,0 +1,61 @@
+use crate::asteroid::AsteroidHit;
+
+/// Points for destroying a large (stage 2) asteroid.
+pub const POINTS_LARGE: u32 = 20;
+
+/// Points for destroying a medium (stage 1) asteroid.
+pub const POINTS_MEDIUM: u32 = 50;
+
+/// Points for destroying a small (stage 0) asteroid.
+pub const POINTS_SMALL: u32 = 100;
+
+/// Player score; smaller asteroids are harder to hit and award more points.
+#[derive(Default)]
+pub struct Score {
+    pub points: u32,
+}
+
+impl Score {
+    /// Points awarded for destroying an asteroid of the given split stage.
+    #[inline]
+    pub const fn points_for_stage(split_stage: u8) -> u32 {
+        match split_stage {
+            0 => POINTS_SMALL,
+            1 => POINTS_MEDIUM,
+            _ => POINTS_LARGE,
+        }
+    }
+
+    /// Add the points for all asteroids destroyed this frame.
+    pub fn award(&mut self, hits: &[AsteroidHit]) {
+        for hit in hits {
+            self.points = self
+                .points
+                .saturating_add(Self::points_for_stage(hit.split_stage));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hit(split_stage: u8) -> AsteroidHit {
+        AsteroidHit {
+            x: 0.0,
+            y: 0.0,
+            radius: 10.0,
+            split_stage,
+        }
+    }
+
+    #[test]
+    fn smaller_asteroids_award_more_points() {
+        let mut score = Score::default();
+        score.award(&[hit(2), hit(1), hit(0), hit(0)]);
+        assert_eq!(
+            score.points,
+            POINTS_LARGE + POINTS_MEDIUM + 2 * POINTS_SMALL
+        );
+    }
+}
@@ -0,0 +1,392 @@
+use crate::rand::XorShift64;
+use libm::{cosf, expf, sinf, sqrtf};
+
+/// Number of ships the player starts with (including the one on screen).
+pub const START_LIVES: u32 = 3;
+
+/// Seconds the ship stays destroyed before a respawn is attempted.
+pub const RESPAWN_DELAY: f32 = 1.5;
+
+/// Seconds of invulnerability granted after a respawn.
+pub const INVULNERABLE_TIME: f32 = 3.0;
+
+/// Duration of a single on or off phase of the invulnerability blink, in seconds.
+const BLINK_INTERVAL: f32 = 0.125;
+
+/// Radius around the screen center that must be free of asteroids before respawning.
+pub const SPAWN_CLEAR_RADIUS: f32 = 100.0;
+
+/// Fraction of the forward acceleration available for braking in Newtonian mode.
+const REVERSE_THRUST_FACTOR: f32 = 0.5;
+
+/// How thrust moves the ship.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FlightModel {
+    /// Classic inertia: thrust accelerates along the nose, and the ship keeps drifting
+    /// in its direction of travel while turning.
+    #[default]
+    Newtonian,
+    /// Thrust sets the speed directly and the ship always moves where its nose points.
+    Arcade,
+}
+
+/// Player ship representation and behavior
+pub struct Ship {
+    pub x: f32,
+    pub y: f32,
+    pub angle: f32, // radians; 0 faces +Y (downwards)
+    pub vx: f32,    // velocity, pixels/second
+    pub vy: f32,    // velocity, pixels/second
+    pub tri_h: f32, // distance from center to nose along forward
+    pub tri_w: f32, // base width
+    pub flight_model: FlightModel,
+    pub thrust: f32,             // arcade: speed when thrusting, pixels/second
+    pub friction: f32,           // arcade: exponential speed decay rate, 1/second
+    pub acceleration: f32,       // Newtonian: thrust acceleration, pixels/second²
+    pub drag: f32,               // Newtonian: exponential velocity decay rate, 1/second
+    pub max_speed: f32,          // Newtonian: speed cap, pixels/second
+    pub reverse_thrust: bool,    // Newtonian: whether reverse thrust brakes
+    pub rot_speed: f32,          // radians per second
+    pub lives: u32,              // remaining ships, including the current one
+    pub alive: bool,             // false while waiting for respawn
+    pub respawn_timer: f32,      // seconds until a respawn may be attempted
+    pub invulnerable_timer: f32, // seconds of remaining invulnerability
+}
+
+impl Ship {
+    /// Construct a ship centered on screen with default tuning.
+    pub fn new(sw: usize, sh: usize) -> Self {
+        Ship {
+            x: (sw / 2) as f32,
+            y: (sh / 2) as f32,
+            angle: 0.0,
+            vx: 0.0,
+            vy: 0.0,
+            tri_h: 24.0,
+            tri_w: 18.0,
+            flight_model: FlightModel::default(),
+            thrust: 90.0,
+            friction: 9.75, // retains 85% of the speed per 60 Hz frame
+            acceleration: 600.0,
+            drag: 0.5,
+            max_speed: 400.0,
+            reverse_thrust: true,
+            rot_speed: 4.8,
+            lives: START_LIVES,
+            alive: true,
+            respawn_timer: 0.0,
+            invulnerable_timer: 0.0,
+        }
+    }
+
+    /// Forward unit vector (X right, Y down)
+    #[inline]
+    pub fn forward_vec(&self) -> (f32, f32) {
+        (-sinf(self.angle), cosf(self.angle))
+    }
+
+    /// Nose position in world space
+    #[inline]
+    pub fn nose(&self) -> (f32, f32) {
+        let (fx, fy) = self.forward_vec();
+        (self.x + fx * self.tri_h, self.y + fy * self.tri_h)
+    }
+
+    /// Current speed in pixels/second.
+    #[inline]
+    pub fn speed(&self) -> f32 {
+        sqrtf(self.vx * self.vx + self.vy * self.vy)
+    }
+
+    /// Circle approximation of the hull used for asteroid collisions.
+    #[inline]
+    pub fn collision_radius(&self) -> f32 {
+        0.5 * (self.tri_h + 0.5 * self.tri_w)
+    }
+
+    /// Whether asteroids can currently destroy the ship.
+    #[inline]
+    pub const fn is_vulnerable(&self) -> bool {
+        self.alive && self.invulnerable_timer <= 0.0
+    }
+
+    /// Whether the ship should be drawn this frame; blinks while invulnerable.
+    #[inline]
+    pub fn is_visible(&self) -> bool {
+        self.alive && ((self.invulnerable_timer / BLINK_INTERVAL) as u32).is_multiple_of(2)
+    }
+
+    /// True once the last ship has been lost.
+    #[inline]
+    pub const fn is_game_over(&self) -> bool {
+        !self.alive && self.lives == 0
+    }
+
+    /// True if the ship is destroyed, has lives left, and the respawn delay has elapsed.
+    #[inline]
+    pub const fn ready_to_respawn(&self) -> bool {
+        !self.alive && self.lives > 0 && self.respawn_timer <= 0.0
+    }
+
+    /// Destroy the ship, consuming a life and starting the respawn delay.
+    pub fn destroy(&mut self) {
+        self.alive = false;
+        (self.vx, self.vy) = (0.0, 0.0);
+        self.lives = self.lives.saturating_sub(1);
+        self.respawn_timer = RESPAWN_DELAY;
+        self.invulnerable_timer = 0.0;
+    }
+
+    /// Place the ship back at the screen center with a fresh invulnerability window.
+    pub fn respawn(&mut self, sw: usize, sh: usize) {
+        self.x = (sw / 2) as f32;
+        self.y = (sh / 2) as f32;
+        self.angle = 0.0;
+        (self.vx, self.vy) = (0.0, 0.0);
+        self.alive = true;
+        self.invulnerable_timer = INVULNERABLE_TIME;
+    }
+
+    /// Hyperspace jump: reappear at a random position at rest. The destination is not
+    /// checked for asteroids, which is the risk of jumping.
+    pub fn hyperspace(&mut self, rng: &mut XorShift64, sw: f32, sh: f32) {
+        self.x = rng.range_f32(0.0, sw);
+        self.y = rng.range_f32(0.0, sh);
+        (self.vx, self.vy) = (0.0, 0.0);
+    }
+
+    /// Advance the respawn and invulnerability timers by `dt` seconds.
+    #[inline]
+    pub fn tick_timers(&mut self, dt: f32) {
+        if self.alive {
+            self.invulnerable_timer = (self.invulnerable_timer - dt).max(0.0);
+        } else {
+            self.respawn_timer = (self.respawn_timer - dt).max(0.0);
+        }
+    }
+
+    /// Update rotation, thrust, integrate motion over `dt` seconds according to the
+    /// flight model, apply drag or friction, and wrap. rot: -1, 0, 1; thr: -1, 0, 1
+    pub fn update(&mut self, rot: i8, thr: i8, dt: f32, sw: f32, sh: f32) {
+        // Apply rotation and thrust intent
+        self.angle += (rot as f32) * self.rot_speed * dt;
+        let decay_rate = match self.flight_model {
+            FlightModel::Newtonian => self.thrust_newtonian(thr, dt),
+            FlightModel::Arcade => self.thrust_arcade(thr),
+        };
+        // Integrate, then let the velocity decay
+        self.x += self.vx * dt;
+        self.y += self.vy * dt;
+        let decay = expf(-decay_rate * dt);
+        self.vx *= decay;
+        self.vy *= decay;
+        // Wrap
+        if self.x < 0.0 {
+            self.x += sw;
+        }
+        if self.y < 0.0 {
+            self.y += sh;
+        }
+        if self.x >= sw {
+            self.x -= sw;
+        }
+        if self.y >= sh {
+            self.y -= sh;
+        }
+    }
+
+    /// Accelerate along the nose and cap the speed; returns the drag rate.
+    fn thrust_newtonian(&mut self, thr: i8, dt: f32) -> f32 {
+        if thr > 0 {
+            let (fx, fy) = self.forward_vec();
+            self.vx += fx * self.acceleration * dt;
+            self.vy += fy * self.acceleration * dt;
+            let speed = self.speed();
+            if speed > self.max_speed {
+                let k = self.max_speed / speed;
+                self.vx *= k;
+                self.vy *= k;
+            }
+        } else if thr < 0 && self.reverse_thrust {
+            // Brake against the direction of travel, coming to rest instead of reversing
+            let speed = self.speed();
+            if speed > 0.0 {
+                let braked = speed - self.acceleration * REVERSE_THRUST_FACTOR * dt;
+                let k = braked.max(0.0) / speed;
+                self.vx *= k;
+                self.vy *= k;
+            }
+        }
+        self.drag
+    }
+
+    /// Point the velocity along the (already rotated) nose, setting the speed when thrusting;
+    /// returns the friction rate.
+    fn thrust_arcade(&mut self, thr: i8) -> f32 {
+        // The velocity was parallel to the nose before this step's (small) turn,
+        // so only its direction along the nose needs to be recovered
+        let (fx, fy) = self.forward_vec();
+        let speed = if thr != 0 {
+            (thr as f32) * self.thrust
+        } else if self.vx * fx + self.vy * fy < 0.0 {
+            -self.speed()
+        } else {
+            self.speed()
+        };
+        (self.vx, self.vy) = (fx * speed, fy * speed);
+        self.friction
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hyperspace_lands_on_screen_at_rest() {
+        let mut ship = Ship::new(800, 600);
+        let mut rng = XorShift64::new(3);
+        ship.vx = 100.0;
+        for _ in 0..50 {
+            ship.hyperspace(&mut rng, 800.0, 600.0);
+            assert!((0.0..800.0).contains(&ship.x) && (0.0..600.0).contains(&ship.y));
+        }
+        assert_eq!((ship.vx, ship.vy), (0.0, 0.0));
+    }
+
+    #[test]
+    fn update_wraps_around_screen_edges() {
+        let mut ship = Ship::new(800, 600);
+        ship.x = 799.0;
+        ship.angle = -core::f32::consts::FRAC_PI_2; // facing +X
+        ship.update(0, 1, 0.1, 800.0, 600.0);
+        assert!(ship.x < 10.0, "x = {}", ship.x);
+        assert!(ship.y >= 0.0 && ship.y < 600.0);
+
+        ship.y = 1.0;
+        ship.angle = core::f32::consts::PI; // facing -Y
+        ship.update(0, 1, 0.1, 800.0, 600.0);
+        assert!(ship.y > 590.0, "y = {}", ship.y);
+    }
+
+    #[test]
+    fn friction_slows_the_ship_down() {
+        for model in [FlightModel::Arcade, FlightModel::Newtonian] {
+            let mut ship = Ship::new(800, 600);
+            ship.flight_model = model;
+            ship.update(0, 1, 1.0 / 60.0, 800.0, 600.0);
+            let v0 = ship.speed();
+            ship.update(0, 0, 1.0 / 60.0, 800.0, 600.0);
+            assert!(ship.speed() < v0 && ship.speed() > 0.0, "{model:?}");
+        }
+    }
+
+    #[test]
+    fn arcade_ship_turns_like_a_car() {
+        let mut ship = Ship::new(800, 600);
+        ship.flight_model = FlightModel::Arcade;
+        ship.update(0, 1, 1.0 / 60.0, 800.0, 600.0);
+        for _ in 0..10 {
+            ship.update(1, 0, 1.0 / 60.0, 800.0, 600.0);
+        }
+        // Still moving exactly along the nose
+        let (fx, fy) = ship.forward_vec();
+        let along = ship.vx * fx + ship.vy * fy;
+        assert!((along - ship.speed()).abs() < 1e-3);
+    }
+
+    #[test]
+    fn newtonian_ship_drifts_while_turning() {
+        let mut ship = Ship::new(800, 600);
+        ship.drag = 0.0;
+        for _ in 0..30 {
+            ship.update(0, 1, 1.0 / 60.0, 800.0, 600.0);
+        }
+        let (vx, vy) = (ship.vx, ship.vy);
+        // Turning without thrust keeps the velocity
+        for _ in 0..20 {
+            ship.update(1, 0, 1.0 / 60.0, 800.0, 600.0);
+        }
+        assert_eq!((ship.vx, ship.vy), (vx, vy));
+    }
+
+    #[test]
+    fn newtonian_speed_is_capped_and_reverse_thrust_is_optional() {
+        let mut ship = Ship::new(800, 600);
+        for _ in 0..600 {
+            ship.update(0, 1, 1.0 / 60.0, 800.0, 600.0);
+        }
+        assert!(ship.speed() <= ship.max_speed + 1e-3);
+
+        let v0 = ship.speed();
+        ship.drag = 0.0;
+        ship.reverse_thrust = false;
+        ship.update(0, -1, 1.0 / 60.0, 800.0, 600.0);
+        assert_eq!(ship.speed(), v0);
+        ship.reverse_thrust = true;
+        ship.update(0, -1, 1.0 / 60.0, 800.0, 600.0);
+        assert!(ship.speed() < v0);
+    }
+
+    #[test]
+    fn reverse_thrust_brakes_to_a_stop() {
+        let mut ship = Ship::new(800, 600);
+        ship.drag = 0.0;
+        // Drifting sideways relative to the nose
+        (ship.vx, ship.vy) = (50.0, 0.0);
+        for _ in 0..600 {
+            ship.update(0, -1, 1.0 / 60.0, 800.0, 600.0);
+        }
+        assert_eq!((ship.vx, ship.vy), (0.0, 0.0));
+    }
+
+    #[test]
+    fn destroy_and_respawn_cycle() {
+        let mut ship = Ship::new(800, 600);
+        ship.x = 10.0;
+        ship.destroy();
+        assert!(!ship.alive && !ship.is_visible() && !ship.is_vulnerable());
+        assert_eq!(ship.lives, START_LIVES - 1);
+        assert!(!ship.ready_to_respawn());
+
+        ship.tick_timers(RESPAWN_DELAY);
+        assert!(ship.ready_to_respawn());
+        ship.respawn(800, 600);
+        assert!(ship.alive && !ship.is_vulnerable());
+        assert_eq!((ship.x, ship.y), (400.0, 300.0));
+
+        ship.tick_timers(INVULNERABLE_TIME);
+        assert!(ship.is_vulnerable() && ship.is_visible());
+    }
+
+    #[test]
+    fn ship_blinks_while_invulnerable() {
+        let mut ship = Ship::new(800, 600);
+        ship.destroy();
+        ship.respawn(800, 600);
+        let mut visible = 0;
+        let mut hidden = 0;
+        while !ship.is_vulnerable() {
+            if ship.is_visible() {
+                visible += 1;
+            } else {
+                hidden += 1;
+            }
+            ship.tick_timers(1.0 / 60.0);
+        }
+        assert!(visible > 0 && hidden > 0);
+    }
+
+    #[test]
+    fn game_over_after_last_life() {
+        let mut ship = Ship::new(800, 600);
+        for _ in 0..START_LIVES {
+            assert!(!ship.is_game_over());
+            ship.respawn(800, 600);
+            ship.destroy();
+            ship.tick_timers(RESPAWN_DELAY);
+        }
+        assert!(ship.is_game_over());
+        assert!(!ship.ready_to_respawn());
+    }
+}
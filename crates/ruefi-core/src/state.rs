@@ -0,0 +1,250 @@
+//! The game's top-level states and the title and pause menus that move between them.
+
+/// Top-level state: Title → Playing ⇄ Paused → GameOver → Title.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GameState {
+    #[default]
+    Title,
+    Playing,
+    Paused,
+    GameOver,
+}
+
+/// Something that moves the game to another state.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StateEvent {
+    /// Start a game from the title screen, or restart from the pause menu.
+    NewGame,
+    Pause,
+    Resume,
+    /// The last ship was destroyed.
+    ShipsLost,
+    /// Leave the game-over screen.
+    Continue,
+}
+
+impl GameState {
+    /// The state after `event`; events that do not apply leave the state unchanged.
+    pub fn next(self, event: StateEvent) -> Self {
+        use GameState::*;
+        match (self, event) {
+            (Title | Paused, StateEvent::NewGame) => Playing,
+            (Playing, StateEvent::Pause) => Paused,
+            (Paused, StateEvent::Resume) => Playing,
+            (Playing, StateEvent::ShipsLost) => GameOver,
+            (GameOver, StateEvent::Continue) => Title,
+            (state, _) => state,
+        }
+    }
+
+    /// Whether the world is simulated; after game over, the asteroids keep drifting.
+    #[inline]
+    pub fn simulates(self) -> bool {
+        matches!(self, GameState::Playing | GameState::GameOver)
+    }
+
+    /// Whether the title or pause menu is shown.
+    #[inline]
+    pub fn has_menu(self) -> bool {
+        matches!(self, GameState::Title | GameState::Paused)
+    }
+}
+
+/// Pages of the title and pause menus.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MenuPage {
+    #[default]
+    Main,
+    Settings,
+    HighScores,
+    Controls,
+    VideoMode,
+}
+
+/// Entries of the main and settings pages.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MenuItem {
+    Play,
+    Resume,
+    Restart,
+    Settings,
+    HighScores,
+    ExitToFirmware,
+    Controls,
+    VideoMode,
+    Persistence,
+    Bloom,
+    Scanlines,
+    Back,
+}
+
+impl MenuItem {
+    pub const fn label(self) -> &'static str {
+        match self {
+            MenuItem::Play => "PLAY",
+            MenuItem::Resume => "RESUME",
+            MenuItem::Restart => "RESTART",
+            MenuItem::Settings => "SETTINGS",
+            MenuItem::HighScores => "HIGH SCORES",
+            MenuItem::ExitToFirmware => "EXIT TO FIRMWARE",
+            MenuItem::Controls => "CONTROLS",
+            MenuItem::VideoMode => "VIDEO MODE",
+            MenuItem::Persistence => "PHOSPHOR PERSISTENCE",
+            MenuItem::Bloom => "BLOOM",
+            MenuItem::Scanlines => "SCANLINES",
+            MenuItem::Back => "BACK",
+        }
+    }
+}
+
+const TITLE_ITEMS: [MenuItem; 4] = [
+    MenuItem::Play,
+    MenuItem::Settings,
+    MenuItem::HighScores,
+    MenuItem::ExitToFirmware,
+];
+
+const PAUSE_ITEMS: [MenuItem; 5] = [
+    MenuItem::Resume,
+    MenuItem::Restart,
+    MenuItem::Settings,
+    MenuItem::HighScores,
+    MenuItem::ExitToFirmware,
+];
+
+const SETTINGS_ITEMS: [MenuItem; 6] = [
+    MenuItem::Controls,
+    MenuItem::VideoMode,
+    MenuItem::Persistence,
+    MenuItem::Bloom,
+    MenuItem::Scanlines,
+    MenuItem::Back,
+];
+
+/// Navigation within the title and pause menus. The controls and video mode pages are
+/// lists of their own; for the latter, `selected` is the highlighted mode.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Menu {
+    pub page: MenuPage,
+    pub selected: usize,
+}
+
+impl Menu {
+    /// The entries of the current page; empty for pages that are not lists of [`MenuItem`]s.
+    pub fn items(&self, state: GameState) -> &'static [MenuItem] {
+        match (self.page, state) {
+            (MenuPage::Main, GameState::Paused) => &PAUSE_ITEMS,
+            (MenuPage::Main, _) => &TITLE_ITEMS,
+            (MenuPage::Settings, _) => &SETTINGS_ITEMS,
+            _ => &[],
+        }
+    }
+
+    /// The highlighted entry, if the page lists [`MenuItem`]s.
+    pub fn selected_item(&self, state: GameState) -> Option<MenuItem> {
+        self.items(state).get(self.selected).copied()
+    }
+
+    /// Move the highlight by `delta` entries within a page of `len` entries.
+    pub fn move_selection(&mut self, delta: isize, len: usize) {
+        self.selected = self
+            .selected
+            .saturating_add_signed(delta)
+            .min(len.saturating_sub(1));
+    }
+
+    /// Switch to a page, highlighting its entry `selected`.
+    pub fn open(&mut self, page: MenuPage, selected: usize) {
+        *self = Self { page, selected };
+    }
+
+    /// Return to the parent page, highlighting the entry that led here;
+    /// `false` on the main page, which has no parent.
+    pub fn back(&mut self, state: GameState) -> bool {
+        let (page, item) = match self.page {
+            MenuPage::Main => return false,
+            MenuPage::Settings => (MenuPage::Main, MenuItem::Settings),
+            MenuPage::HighScores => (MenuPage::Main, MenuItem::HighScores),
+            MenuPage::Controls => (MenuPage::Settings, MenuItem::Controls),
+            MenuPage::VideoMode => (MenuPage::Settings, MenuItem::VideoMode),
+        };
+        self.open(page, 0);
+        self.selected = self
+            .items(state)
+            .iter()
+            .position(|&i| i == item)
+            .unwrap_or(0);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn states_follow_the_game_flow() {
+        let mut state = GameState::default();
+        for (event, expected) in [
+            (StateEvent::Pause, GameState::Title),
+            (StateEvent::NewGame, GameState::Playing),
+            (StateEvent::Pause, GameState::Paused),
+            (StateEvent::ShipsLost, GameState::Paused),
+            (StateEvent::Resume, GameState::Playing),
+            (StateEvent::ShipsLost, GameState::GameOver),
+            (StateEvent::Resume, GameState::GameOver),
+            (StateEvent::Continue, GameState::Title),
+        ] {
+            state = state.next(event);
+            assert_eq!(state, expected, "after {event:?}");
+        }
+        assert_eq!(
+            GameState::Paused.next(StateEvent::NewGame),
+            GameState::Playing
+        );
+    }
+
+    #[test]
+    fn main_menu_depends_on_the_state() {
+        let menu = Menu::default();
+        assert_eq!(menu.selected_item(GameState::Title), Some(MenuItem::Play));
+        assert_eq!(
+            menu.selected_item(GameState::Paused),
+            Some(MenuItem::Resume)
+        );
+        assert!(
+            menu.items(GameState::Paused)
+                .contains(&MenuItem::ExitToFirmware)
+        );
+    }
+
+    #[test]
+    fn back_returns_to_the_entry_that_opened_a_page() {
+        let mut menu = Menu::default();
+        menu.open(MenuPage::VideoMode, 3);
+        assert!(menu.back(GameState::Title));
+        assert_eq!(menu.page, MenuPage::Settings);
+        assert_eq!(
+            menu.selected_item(GameState::Title),
+            Some(MenuItem::VideoMode)
+        );
+        assert!(menu.back(GameState::Title));
+        assert_eq!(
+            menu.selected_item(GameState::Title),
+            Some(MenuItem::Settings)
+        );
+        assert!(!menu.back(GameState::Title));
+
+        menu.open(MenuPage::HighScores, 0);
+        assert!(menu.back(GameState::Paused));
+        assert_eq!(
+            menu.selected_item(GameState::Paused),
+            Some(MenuItem::HighScores)
+        );
+
+        menu.move_selection(10, 5);
+        assert_eq!(menu.selected, 4);
+        menu.move_selection(-10, 5);
+        assert_eq!(menu.selected, 0);
+    }
+}
@@ -0,0 +1,142 @@
+use crate::asteroid::Asteroid;
+use crate::rand::XorShift64;
+use alloc::vec::Vec;
+
+extern crate alloc;
+
+/// Difficulty curve of the asteroid waves; all wave tuning lives here.
+#[derive(Clone)]
+pub struct WaveConfig {
+    pub first_count: u32, // asteroids in the first wave
+    pub count_step: u32,  // additional asteroids per wave
+    pub max_count: u32,   // upper bound on asteroids per wave
+    pub first_speed: f32, // asteroid speed multiplier in the first wave
+    pub speed_step: f32,  // speed multiplier added per wave
+    pub max_speed: f32,   // upper bound on the speed multiplier
+    pub banner_time: f32, // seconds the "Wave N" banner is shown before spawning
+}
+
+impl Default for WaveConfig {
+    fn default() -> Self {
+        Self {
+            first_count: 5,
+            count_step: 1,
+            max_count: 12,
+            first_speed: 1.0,
+            speed_step: 0.15,
+            max_speed: 2.5,
+            banner_time: 2.0,
+        }
+    }
+}
+
+impl WaveConfig {
+    /// Number of asteroids spawned in the given (1-based) wave.
+    #[inline]
+    pub fn asteroid_count(&self, wave: u32) -> u32 {
+        let extra = self.count_step.saturating_mul(wave.saturating_sub(1));
+        self.first_count.saturating_add(extra).min(self.max_count)
+    }
+
+    /// Asteroid speed multiplier for the given (1-based) wave.
+    #[inline]
+    pub fn speed_scale(&self, wave: u32) -> f32 {
+        let extra = self.speed_step * (wave.saturating_sub(1) as f32);
+        (self.first_speed + extra).min(self.max_speed)
+    }
+}
+
+/// Tracks the current wave and spawns the next one once the field is cleared.
+pub struct WaveManager {
+    pub config: WaveConfig,
+    pub wave: u32,     // current wave; 0 before the first wave was announced
+    pending: bool,     // true while the announced wave has not spawned yet
+    banner_timer: f32, // seconds until the announced wave spawns
+}
+
+impl WaveManager {
+    pub fn new(config: WaveConfig) -> Self {
+        Self {
+            config,
+            wave: 0,
+            pending: false,
+            banner_timer: 0.0,
+        }
+    }
+
+    /// The wave currently being announced, if its banner should be shown.
+    #[inline]
+    pub const fn banner(&self) -> Option<u32> {
+        if self.pending { Some(self.wave) } else { None }
+    }
+
+    /// Advance by `dt` seconds. When no asteroids are left, the next wave is announced
+    /// and spawned after the banner delay.
+    pub fn update(
+        &mut self,
+        dt: f32,
+        asteroids: &mut Vec<Asteroid>,
+        rng: &mut XorShift64,
+        sw: usize,
+        sh: usize,
+    ) {
+        if !self.pending && asteroids.is_empty() {
+            self.wave += 1;
+            self.pending = true;
+            self.banner_timer = self.config.banner_time;
+        }
+        if self.pending {
+            self.banner_timer -= dt;
+            if self.banner_timer <= 0.0 {
+                self.pending = false;
+                let speed_scale = self.config.speed_scale(self.wave);
+                for _ in 0..self.config.asteroid_count(self.wave) {
+                    asteroids.push(Asteroid::random_spawn(rng, sw, sh, speed_scale));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn waves_get_larger_and_faster_up_to_the_cap() {
+        let config = WaveConfig::default();
+        assert_eq!(config.asteroid_count(1), config.first_count);
+        assert_eq!(
+            config.asteroid_count(2),
+            config.first_count + config.count_step
+        );
+        assert_eq!(config.asteroid_count(1000), config.max_count);
+        assert_eq!(config.speed_scale(1), config.first_speed);
+        assert!(config.speed_scale(3) > config.speed_scale(2));
+        assert_eq!(config.speed_scale(1000), config.max_speed);
+    }
+
+    #[test]
+    fn next_wave_spawns_after_banner() {
+        let mut rng = XorShift64::new(11);
+        let mut waves = WaveManager::new(WaveConfig::default());
+        let mut asteroids = Vec::new();
+
+        waves.update(0.5, &mut asteroids, &mut rng, 800, 600);
+        assert_eq!(waves.wave, 1);
+        assert_eq!(waves.banner(), Some(1));
+        assert!(asteroids.is_empty());
+
+        waves.update(waves.config.banner_time, &mut asteroids, &mut rng, 800, 600);
+        assert_eq!(waves.banner(), None);
+        assert_eq!(asteroids.len() as u32, waves.config.asteroid_count(1));
+
+        // Nothing happens while asteroids are left
+        waves.update(10.0, &mut asteroids, &mut rng, 800, 600);
+        assert_eq!(waves.wave, 1);
+
+        asteroids.clear();
+        waves.update(0.0, &mut asteroids, &mut rng, 800, 600);
+        assert_eq!(waves.banner(), Some(2));
+    }
+}
@@ -0,0 +1,118 @@
+use image::ImageReader;
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+/// Size of a single glyph cell in the font sheet, including spacing.
+const FONT_CELL_W: u32 = 6;
+const FONT_CELL_H: u32 = 8;
+
+/// First character in the font sheet; glyphs follow in ASCII order, row by row.
+const FONT_FIRST_CHAR: u8 = b' ';
+
+/// Number of glyphs in the font sheet (printable ASCII).
+const FONT_GLYPH_COUNT: u32 = 95;
+
+const _: () = assert!(FONT_CELL_W <= 8, "font glyphs must fit into a byte per row");
+
+fn main() {
+    // Input asset(s)
+    let in_png = PathBuf::from("../../assets/ruefi.png");
+    println!("cargo:rerun-if-changed={}", in_png.display());
+
+    // Load + decode PNG using the `image` crate
+    let img = ImageReader::open(&in_png)
+        .unwrap()
+        .decode()
+        .unwrap()
+        .to_rgba8();
+    let (w, h) = img.dimensions();
+    let mut bytes = img.into_raw(); // RGBA8
+
+    // Premultiply alpha: RGB' = RGB * A/255 (leave A unchanged), as expected by `blit_rgba`
+    for px in bytes.chunks_mut(4) {
+        // RGBA order
+        let a = px[3] as u16;
+        px[0] = ((px[0] as u16 * a) / 255) as u8; // R'
+        px[1] = ((px[1] as u16 * a) / 255) as u8; // G'
+        px[2] = ((px[2] as u16 * a) / 255) as u8; // B'
+        // px[3] unchanged
+    }
+
+    // Write raw bytes for include_bytes!
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    let raw_path = out_dir.join("logo.rgba"); // width*w, height*h, 4*bpp
+    fs::write(&raw_path, &bytes).unwrap();
+
+    // Generate a tiny Rust module that references the bytes
+    let generated = format!(
+        r#"
+        // Auto-generated. Do not edit.
+        #[allow(non_upper_case_globals)]
+        pub const LOGO_WIDTH: usize = {w};
+        #[allow(non_upper_case_globals)]
+        pub const LOGO_HEIGHT: usize = {h};
+        pub const LOGO_RGBA: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/logo.rgba"));
+        "#,
+        w = w as usize,
+        h = h as usize
+    );
+    fs::write(out_dir.join("assets_gen.rs"), generated).unwrap();
+
+    generate_font(&out_dir);
+}
+
+/// Convert the font sheet into one byte per glyph row (bit 7 = leftmost pixel).
+/// A pixel is considered set if it is opaque and bright.
+fn generate_font(out_dir: &Path) {
+    let in_png = PathBuf::from("../../assets/font.png");
+    println!("cargo:rerun-if-changed={}", in_png.display());
+
+    let img = ImageReader::open(&in_png)
+        .unwrap()
+        .decode()
+        .unwrap()
+        .to_rgba8();
+    let (w, h) = img.dimensions();
+    let cols = w / FONT_CELL_W;
+    let rows = h / FONT_CELL_H;
+    assert!(
+        cols * rows >= FONT_GLYPH_COUNT,
+        "font sheet too small for {FONT_GLYPH_COUNT} glyphs"
+    );
+
+    let mut bits = Vec::with_capacity((FONT_GLYPH_COUNT * FONT_CELL_H) as usize);
+    for glyph in 0..FONT_GLYPH_COUNT {
+        let cx = (glyph % cols) * FONT_CELL_W;
+        let cy = (glyph / cols) * FONT_CELL_H;
+        for y in 0..FONT_CELL_H {
+            let mut row = 0u8;
+            for x in 0..FONT_CELL_W {
+                let [r, g, b, a] = img.get_pixel(cx + x, cy + y).0;
+                let luma = (r as u16 + g as u16 + b as u16) / 3;
+                if a >= 128 && luma >= 128 {
+                    row |= 0x80 >> x;
+                }
+            }
+            bits.push(row);
+        }
+    }
+    fs::write(out_dir.join("font.bin"), &bits).unwrap();
+
+    let generated = format!(
+        r#"
+        // Auto-generated. Do not edit.
+        pub const FONT_GLYPH_W: usize = {cw};
+        pub const FONT_GLYPH_H: usize = {ch};
+        pub const FONT_FIRST_CHAR: u8 = {first};
+        pub const FONT_GLYPH_COUNT: usize = {count};
+        pub const FONT_BITMAP: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/font.bin"));
+        "#,
+        cw = FONT_CELL_W,
+        ch = FONT_CELL_H,
+        first = FONT_FIRST_CHAR,
+        count = FONT_GLYPH_COUNT
+    );
+    fs::write(out_dir.join("font_gen.rs"), generated).unwrap();
+}
@@ -0,0 +1,1633 @@
+extern crate alloc;
+use crate::dirty::{DirtyRegion, Rect};
+use crate::flush::{self, FlushPath, FrameStats};
+use crate::font::{self, FONT_GLYPH_H, FONT_GLYPH_W};
+use crate::png::Image;
+use alloc::vec::Vec;
+use core::fmt::Write;
+use libm::{ceilf, cosf, fabsf, floorf, roundf, sinf, sqrtf};
+use ruefi_core::asteroid::Asteroid;
+use ruefi_core::highscore::{HighScoreTable, InitialsEntry};
+use ruefi_core::particle::Particle;
+use ruefi_core::ship::Ship;
+
+/// Distance of the HUD from the top and right screen edges.
+const HUD_MARGIN: isize = 10;
+
+/// Integer scale factor of HUD text.
+const HUD_TEXT_SCALE: usize = 2;
+
+/// Integer scale factors of menu titles and items, and the gap between item rows.
+const MENU_TITLE_SCALE: usize = 3;
+const MENU_ITEM_SCALE: usize = 2;
+const MENU_ROW_GAP: isize = 6;
+
+/// Fill and border colors of UI panels.
+const PANEL_FILL: (u8, u8, u8) = (16, 16, 40);
+const PANEL_BORDER: (u8, u8, u8) = (90, 90, 150);
+
+/// Space between a panel's border and its contents.
+const PANEL_PADDING: isize = 12;
+
+/// Integer scale factor of the initials on the initials-entry screen.
+const INITIALS_SCALE: usize = 4;
+
+/// How sprites are sampled when rotated or scaled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Sampling {
+    /// Nearest texel; crisp, blocky pixel art.
+    Nearest,
+    /// Weighted average of the four nearest texels; smooth edges.
+    Bilinear,
+}
+
+/// Width and anti-aliasing of lines, chosen per draw call.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LineStyle {
+    /// Line width in pixels; wider lines get round caps.
+    pub width: f32,
+    /// Blend edge pixels by coverage (Wu's algorithm for 1 px lines) instead of hard edges.
+    pub anti_aliased: bool,
+}
+
+impl LineStyle {
+    /// Classic 1 px Bresenham line.
+    pub const HAIRLINE: Self = Self {
+        width: 1.0,
+        anti_aliased: false,
+    };
+
+    /// Anti-aliased line of the given width.
+    pub const fn smooth(width: f32) -> Self {
+        Self {
+            width,
+            anti_aliased: true,
+        }
+    }
+}
+
+impl Default for LineStyle {
+    fn default() -> Self {
+        Self::HAIRLINE
+    }
+}
+
+/// Which regions of a self-intersecting or nested polygon are filled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FillRule {
+    /// Inside if a ray from the point crosses the outline an odd number of times.
+    EvenOdd,
+    /// Inside if the outline winds around the point at least once.
+    NonZero,
+}
+
+/// Horizontal alignment of text relative to the anchor position.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TextAlign {
+    /// Text starts at the anchor.
+    Left,
+    /// Text is centered on the anchor.
+    Center,
+    /// Text ends at the anchor.
+    Right,
+}
+
+/// Pixel layout of the target framebuffer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PixelFormat {
+    /// 32 bpp, bytes in memory: red, green, blue, reserved.
+    Rgb,
+    /// 32 bpp, bytes in memory: blue, green, red, reserved.
+    Bgr,
+    /// Channels located by bit masks within a little-endian pixel, e.g. a GOP `PixelBitmask` mode.
+    /// The pixel size is derived from the highest bit set in any mask.
+    Bitmask {
+        red: u32,
+        green: u32,
+        blue: u32,
+        reserved: u32,
+    },
+}
+
+impl PixelFormat {
+    /// Channel bit masks as (red, green, blue, reserved).
+    pub const fn masks(&self) -> (u32, u32, u32, u32) {
+        match *self {
+            PixelFormat::Rgb => (0x0000_00FF, 0x0000_FF00, 0x00FF_0000, 0xFF00_0000),
+            PixelFormat::Bgr => (0x00FF_0000, 0x0000_FF00, 0x0000_00FF, 0xFF00_0000),
+            PixelFormat::Bitmask {
+                red,
+                green,
+                blue,
+                reserved,
+            } => (red, green, blue, reserved),
+        }
+    }
+
+    /// Size of a pixel in the target framebuffer, in bytes (1 to 4).
+    pub const fn bytes_per_pixel(&self) -> usize {
+        let (r, g, b, x) = self.masks();
+        let bits = 32 - (r | g | b | x).leading_zeros();
+        if bits == 0 {
+            4
+        } else {
+            bits.div_ceil(8) as usize
+        }
+    }
+}
+
+/// Position and width of a color channel within a packed pixel, derived from its bit mask.
+#[derive(Clone, Copy)]
+struct Channel {
+    shift: u32,
+    bits: u32,
+}
+
+impl Channel {
+    const fn from_mask(mask: u32) -> Self {
+        if mask == 0 {
+            return Channel { shift: 0, bits: 0 };
+        }
+        let shift = mask.trailing_zeros();
+        Channel {
+            shift,
+            bits: (mask >> shift).trailing_ones(),
+        }
+    }
+
+    /// Scale an 8-bit channel value to the channel width and move it into place.
+    #[inline(always)]
+    const fn pack(self, c: u8) -> u32 {
+        let c = c as u32;
+        let v = if self.bits >= 8 {
+            c << (self.bits - 8)
+        } else {
+            c >> (8 - self.bits)
+        };
+        v << self.shift
+    }
+
+    /// Extract the channel from a packed pixel and scale it back to 8 bits.
+    #[inline]
+    const fn unpack(self, px: u32) -> u8 {
+        if self.bits == 0 {
+            return 0;
+        }
+        let max = ((1u64 << self.bits) - 1) as u32;
+        let v = (px >> self.shift) & max;
+        if self.bits >= 8 {
+            (v >> (self.bits - 8)) as u8
+        } else {
+            (v * 255 / max) as u8
+        }
+    }
+}
+
+/// Vertices of the ship's triangular hull in screen space: nose, left and right base.
+fn ship_hull(ship: &Ship) -> [(f32, f32); 3] {
+    // Triangle in local space: nose at (0, +tri_h), base at y = -tri_h/2
+    let half_w = 0.5f32 * ship.tri_w;
+    let verts = [
+        (0.0f32, ship.tri_h),
+        (-half_w, -ship.tri_h * 0.5),
+        (half_w, -ship.tri_h * 0.5),
+    ];
+    let ca = cosf(ship.angle);
+    let sa = sinf(ship.angle);
+    verts.map(|(lx, ly)| (ship.x + lx * ca - ly * sa, ship.y + lx * sa + ly * ca))
+}
+
+/// Vertices of an asteroid's jagged hexagon in screen space (base tile, without wrapping).
+fn asteroid_outline(a: &Asteroid) -> [(f32, f32); 6] {
+    // Precompute cos/sin of base orientation
+    let ca = cosf(a.base_angle);
+    let sa = sinf(a.base_angle);
+    core::array::from_fn(|i| {
+        let t = (i as f32) * (core::f32::consts::PI / 3.0);
+        let rr = a.radius * a.jitter[i];
+
+        // Local coordinates in our basis (X right, Y forward)
+        let lx = -sinf(t) * rr;
+        let ly = cosf(t) * rr;
+
+        // Rotate by base_angle and translate to world (base tile)
+        (a.x + lx * ca - ly * sa, a.y + lx * sa + ly * ca)
+    })
+}
+
+/// Vertical layout of a menu drawn by [`BackBuffer::draw_menu`].
+struct MenuLayout {
+    title_y: isize,
+    list_top: isize, // top of the first visible item
+    row_h: isize,
+    footer_y: isize,
+    first: usize,   // index of the first visible item
+    visible: usize, // number of items that fit on screen
+}
+
+/// `a * b / 255`, rounded to nearest.
+#[inline(always)]
+const fn mul_div255(a: u8, b: u8) -> u8 {
+    let x = a as u32 * b as u32 + 128;
+    ((x + (x >> 8)) >> 8) as u8
+}
+
+/// Software back buffer in system memory with the same drawing API
+pub struct BackBuffer {
+    pub width: usize,
+    pub height: usize,
+    pub format: PixelFormat,
+    pub flush_path: FlushPath, // how `flush_to` copies 32 bpp rows
+    buf: Vec<u32>,             // width * height pixels, each packed in target format
+    // Channel layout used to pack RGB input into the target pixel format
+    channels: [Channel; 3],
+    drawn: DirtyRegion,       // changed since the last clear
+    cleared: DirtyRegion,     // drawn before the last clear, reset to the clear color since
+    clear_color: Option<u32>, // packed color of the last clear, shared by all pixels outside `drawn`
+}
+
+impl BackBuffer {
+    /// Create a black back buffer of the given size, packing pixels for the given target format.
+    pub fn new(width: usize, height: usize, format: PixelFormat) -> Self {
+        let (r, g, b, _) = format.masks();
+        let mut back = BackBuffer {
+            width,
+            height,
+            format,
+            flush_path: FlushPath::detect(),
+            buf: alloc::vec![0; width * height],
+            channels: [
+                Channel::from_mask(r),
+                Channel::from_mask(g),
+                Channel::from_mask(b),
+            ],
+            drawn: DirtyRegion::new(),
+            cleared: DirtyRegion::new(),
+            clear_color: None,
+        };
+        back.mark_all();
+        back
+    }
+
+    /// The parts of the buffer that changed since the frame before the last clear, i.e. what
+    /// needs to be presented: everything drawn before and after the last clear.
+    pub fn dirty_region(&self) -> DirtyRegion {
+        let mut region = self.cleared;
+        region.extend(&self.drawn);
+        region
+    }
+
+    /// Record that the pixels from (`x0`, `y0`) to (`x1`, `y1`) (exclusive) may have changed.
+    #[inline]
+    fn mark(&mut self, x0: isize, y0: isize, x1: isize, y1: isize) {
+        if let Some(rect) = Rect::clipped(x0, y0, x1, y1, self.width, self.height) {
+            self.drawn.add(rect);
+        }
+    }
+
+    /// Record a change of the whole buffer.
+    #[inline]
+    fn mark_all(&mut self) {
+        self.mark(0, 0, self.width as isize, self.height as isize);
+    }
+
+    /// Record that the pixels covered by the given float bounds, plus a margin, may have changed.
+    #[inline]
+    fn mark_f32(&mut self, x0: f32, y0: f32, x1: f32, y1: f32, margin: f32) {
+        let clamp = |v: f32| v.clamp(-1.0e6, 1.0e6);
+        self.mark(
+            floorf(clamp(x0.min(x1) - margin)) as isize,
+            floorf(clamp(y0.min(y1) - margin)) as isize,
+            ceilf(clamp(x0.max(x1) + margin)) as isize + 1,
+            ceilf(clamp(y0.max(y1) + margin)) as isize + 1,
+        );
+    }
+
+    /// Raw pixel data, `width * 4` bytes per row; each pixel is a little-endian `u32`
+    /// in target format. For formats smaller than 32 bpp, only the low bytes are significant.
+    #[inline]
+    pub fn pixels(&self) -> &[u8] {
+        flush::as_bytes(&self.buf)
+    }
+
+    /// Read back the pixel at (x, y) as RGB, regardless of the target pixel format.
+    pub fn get_pixel(&self, x: usize, y: usize) -> (u8, u8, u8) {
+        self.read_rgb(y * self.width + x)
+    }
+
+    /// Unpack the whole buffer into `out` as RGB triplets, row by row.
+    pub(crate) fn read_rgb_all(&self, out: &mut [u8]) {
+        for (i, rgb) in out.chunks_exact_mut(3).enumerate() {
+            let (r, g, b) = self.read_rgb(i);
+            rgb.copy_from_slice(&[r, g, b]);
+        }
+    }
+
+    /// Replace the whole buffer with RGB triplets, row by row.
+    pub(crate) fn write_rgb_all(&mut self, src: &[u8]) {
+        self.mark_all();
+        for (i, rgb) in src.chunks_exact(3).enumerate() {
+            self.buf[i] = self.pack(rgb[0], rgb[1], rgb[2]);
+        }
+    }
+
+    /// Unpack the pixel at index `p` back to RGB.
+    #[inline(always)]
+    fn read_rgb(&self, p: usize) -> (u8, u8, u8) {
+        let px = self.buf[p];
+        let [r, g, b] = self.channels;
+        (r.unpack(px), g.unpack(px), b.unpack(px))
+    }
+
+    /// Pack an RGB color into the target pixel format.
+    #[inline(always)]
+    fn pack(&self, r: u8, g: u8, b: u8) -> u32 {
+        let [cr, cg, cb] = self.channels;
+        cr.pack(r) | cg.pack(g) | cb.pack(b)
+    }
+
+    #[inline]
+    pub fn clear_bgr(&mut self, r: u8, g: u8, b: u8) {
+        self.clear_rgb(b, g, r)
+    }
+
+    /// Fill the buffer with a color. If it matches the previous clear, only the parts drawn
+    /// since then are touched, as everything else already has that color.
+    pub fn clear_rgb(&mut self, r: u8, g: u8, b: u8) {
+        let packed = self.pack(r, g, b);
+        if self.clear_color != Some(packed) {
+            self.mark_all();
+        }
+        for rect in self.drawn.rects() {
+            if rect.width() == self.width {
+                // Full-width rectangles are a single contiguous fill
+                self.buf[rect.y0 * self.width..rect.y1 * self.width].fill(packed);
+                continue;
+            }
+            for y in rect.y0..rect.y1 {
+                let row = y * self.width;
+                self.buf[row + rect.x0..row + rect.x1].fill(packed);
+            }
+        }
+        self.cleared = self.drawn;
+        self.drawn.clear();
+        self.clear_color = Some(packed);
+    }
+
+    #[inline]
+    pub fn put_pixel(&mut self, x: isize, y: isize, r: u8, g: u8, b: u8) {
+        self.mark(x, y, x + 1, y + 1);
+        self.set_pixel(x, y, r, g, b);
+    }
+
+    /// Write a pixel without recording it as dirty; callers mark their whole extent instead.
+    #[inline]
+    fn set_pixel(&mut self, x: isize, y: isize, r: u8, g: u8, b: u8) {
+        if x < 0 || y < 0 {
+            return;
+        }
+        let (x, y) = (x as usize, y as usize);
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        self.buf[y * self.width + x] = self.pack(r, g, b);
+    }
+
+    pub fn draw_line(
+        &mut self,
+        mut x0: isize,
+        mut y0: isize,
+        x1: isize,
+        y1: isize,
+        r: u8,
+        g: u8,
+        b: u8,
+    ) {
+        self.mark(x0.min(x1), y0.min(y1), x0.max(x1) + 1, y0.max(y1) + 1);
+        let dx = (x1 - x0).abs();
+        let sx = if x0 < x1 { 1 } else { -1 };
+        let dy = -(y1 - y0).abs();
+        let sy = if y0 < y1 { 1 } else { -1 };
+        let mut err = dx + dy;
+        // Single path: set_pixel already adapts to the target pixel format
+        loop {
+            self.set_pixel(x0, y0, r, g, b);
+            if x0 == x1 && y0 == y1 {
+                break;
+            }
+            let e2 = 2 * err;
+            if e2 >= dy {
+                err += dy;
+                x0 += sx;
+            }
+            if e2 <= dx {
+                err += dx;
+                y0 += sy;
+            }
+        }
+    }
+
+    /// Draw a line between sub-pixel positions (pixel centers lie at half-integer coordinates)
+    /// with the given width and anti-aliasing.
+    pub fn draw_line_styled(
+        &mut self,
+        x0: f32,
+        y0: f32,
+        x1: f32,
+        y1: f32,
+        style: LineStyle,
+        r: u8,
+        g: u8,
+        b: u8,
+    ) {
+        match style {
+            LineStyle {
+                width: ..=1.0,
+                anti_aliased: false,
+            } => {
+                let (x0, y0) = (floorf(x0) as isize, floorf(y0) as isize);
+                self.draw_line(x0, y0, floorf(x1) as isize, floorf(y1) as isize, r, g, b);
+            }
+            LineStyle {
+                width: ..=1.0,
+                anti_aliased: true,
+            } => self.draw_line_wu(x0 - 0.5, y0 - 0.5, x1 - 0.5, y1 - 0.5, r, g, b),
+            LineStyle {
+                width,
+                anti_aliased,
+            } => self.draw_line_thick(x0, y0, x1, y1, width, anti_aliased, r, g, b),
+        }
+    }
+
+    /// Xiaolin Wu's anti-aliased line; here, pixel centers lie at integer coordinates.
+    fn draw_line_wu(
+        &mut self,
+        mut x0: f32,
+        mut y0: f32,
+        mut x1: f32,
+        mut y1: f32,
+        r: u8,
+        g: u8,
+        b: u8,
+    ) {
+        self.mark_f32(x0, y0, x1, y1, 1.0);
+        // Iterate along the major axis, swapping x and y for steep lines
+        let steep = fabsf(y1 - y0) > fabsf(x1 - x0);
+        if steep {
+            core::mem::swap(&mut x0, &mut y0);
+            core::mem::swap(&mut x1, &mut y1);
+        }
+        if x0 > x1 {
+            core::mem::swap(&mut x0, &mut x1);
+            core::mem::swap(&mut y0, &mut y1);
+        }
+        let gradient = if x1 - x0 > 0.0 {
+            (y1 - y0) / (x1 - x0)
+        } else {
+            1.0
+        };
+        let fract = |v: f32| v - floorf(v);
+        let limit = if steep { self.height } else { self.width } as isize;
+        let mut plot = |major: isize, minor: isize, coverage: f32| {
+            let (x, y) = if steep {
+                (minor, major)
+            } else {
+                (major, minor)
+            };
+            self.plot_coverage(x, y, coverage, r, g, b);
+        };
+
+        // Endpoints, weighted by how much of their pixel the line covers along the major axis
+        let xend0 = roundf(x0);
+        let yend0 = y0 + gradient * (xend0 - x0);
+        let gap0 = 1.0 - fract(x0 + 0.5);
+        let xend1 = roundf(x1);
+        let yend1 = y1 + gradient * (xend1 - x1);
+        let gap1 = fract(x1 + 0.5);
+        for (xend, yend, gap) in [(xend0, yend0, gap0), (xend1, yend1, gap1)] {
+            let (px, py) = (xend as isize, floorf(yend) as isize);
+            plot(px, py, (1.0 - fract(yend)) * gap);
+            plot(px, py + 1, fract(yend) * gap);
+        }
+
+        // Span between the endpoints, limited to the visible part of the major axis
+        let first = (xend0 as isize + 1).max(0);
+        let last = (xend1 as isize).min(limit);
+        for x in first..last {
+            let y = yend0 + gradient * (x as f32 - xend0);
+            let py = floorf(y) as isize;
+            plot(x, py, 1.0 - fract(y));
+            plot(x, py + 1, fract(y));
+        }
+    }
+
+    /// Thick line with round caps: pixels are covered by their center's distance to the segment.
+    fn draw_line_thick(
+        &mut self,
+        x0: f32,
+        y0: f32,
+        x1: f32,
+        y1: f32,
+        width: f32,
+        anti_aliased: bool,
+        r: u8,
+        g: u8,
+        b: u8,
+    ) {
+        let half = 0.5 * width;
+        let pad = half + 1.0;
+        let min_x = floorf(x0.min(x1) - pad).max(0.0) as usize;
+        let min_y = floorf(y0.min(y1) - pad).max(0.0) as usize;
+        let max_x = ceilf(x0.max(x1) + pad).clamp(0.0, self.width as f32) as usize;
+        let max_y = ceilf(y0.max(y1) + pad).clamp(0.0, self.height as f32) as usize;
+        self.mark(
+            min_x as isize,
+            min_y as isize,
+            max_x as isize,
+            max_y as isize,
+        );
+
+        let (dx, dy) = (x1 - x0, y1 - y0);
+        let len2 = dx * dx + dy * dy;
+        for py in min_y..max_y {
+            for px in min_x..max_x {
+                let (cx, cy) = (px as f32 + 0.5, py as f32 + 0.5);
+                let t = if len2 > 0.0 {
+                    (((cx - x0) * dx + (cy - y0) * dy) / len2).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                let (ex, ey) = (cx - (x0 + t * dx), cy - (y0 + t * dy));
+                let dist = sqrtf(ex * ex + ey * ey);
+                let coverage = if anti_aliased {
+                    half + 0.5 - dist
+                } else if dist <= half {
+                    1.0
+                } else {
+                    0.0
+                };
+                self.plot_coverage(px as isize, py as isize, coverage, r, g, b);
+            }
+        }
+    }
+
+    /// Blend a color over the pixel at (x, y) with the given coverage in 0..=1.
+    #[inline]
+    fn plot_coverage(&mut self, x: isize, y: isize, coverage: f32, r: u8, g: u8, b: u8) {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            return;
+        }
+        let a = (coverage.clamp(0.0, 1.0) * 255.0 + 0.5) as u8;
+        let p = y as usize * self.width + x as usize;
+        let (r, g, b) = (mul_div255(r, a), mul_div255(g, a), mul_div255(b, a));
+        self.blend(p, r, g, b, a, 255);
+    }
+
+    pub fn draw_triangle_wire(
+        &mut self,
+        x0: isize,
+        y0: isize,
+        x1: isize,
+        y1: isize,
+        x2: isize,
+        y2: isize,
+        r: u8,
+        g: u8,
+        b: u8,
+    ) {
+        self.draw_line(x0, y0, x1, y1, r, g, b);
+        self.draw_line(x1, y1, x2, y2, r, g, b);
+        self.draw_line(x2, y2, x0, y0, r, g, b);
+    }
+
+    /// Draw the player ship as a rotated triangle centered at (ship.x, ship.y)
+    #[inline]
+    pub fn draw_ship(&mut self, ship: &Ship, style: LineStyle, r: u8, g: u8, b: u8) {
+        self.draw_closed_path(&ship_hull(ship), 0.0, 0.0, style, r, g, b);
+    }
+
+    /// Fill the player ship's hull; draw the outline with [`Self::draw_ship`] on top.
+    pub fn fill_ship(&mut self, ship: &Ship, r: u8, g: u8, b: u8) {
+        self.fill_polygon(&ship_hull(ship), FillRule::NonZero, r, g, b);
+    }
+
+    /// Draw an asteroid as a jagged hexagon with per-vertex jitter and toroidal wrapping (3x3 tiles)
+    pub fn draw_asteroid_wrapped(
+        &mut self,
+        a: &Asteroid,
+        sw: usize,
+        sh: usize,
+        style: LineStyle,
+        r: u8,
+        g: u8,
+        b: u8,
+    ) {
+        let pts_hex = asteroid_outline(a);
+
+        // Draw in a 3x3 neighborhood to emulate wrapping of all vertices/edges
+        let (sw, sh) = (sw as f32, sh as f32);
+        for oy in [-sh, 0.0, sh] {
+            for ox in [-sw, 0.0, sw] {
+                self.draw_closed_path(&pts_hex, ox, oy, style, r, g, b);
+            }
+        }
+    }
+
+    /// Draw the outline of a polygon, offset by (`ox`, `oy`).
+    fn draw_closed_path(
+        &mut self,
+        pts: &[(f32, f32)],
+        ox: f32,
+        oy: f32,
+        style: LineStyle,
+        r: u8,
+        g: u8,
+        b: u8,
+    ) {
+        for (i, &(x0, y0)) in pts.iter().enumerate() {
+            let (x1, y1) = pts[(i + 1) % pts.len()];
+            self.draw_line_styled(x0 + ox, y0 + oy, x1 + ox, y1 + oy, style, r, g, b);
+        }
+    }
+
+    /// Fill an asteroid's hexagon with toroidal wrapping; draw the outline with
+    /// [`Self::draw_asteroid_wrapped`] on top.
+    pub fn fill_asteroid_wrapped(
+        &mut self,
+        a: &Asteroid,
+        sw: usize,
+        sh: usize,
+        r: u8,
+        g: u8,
+        b: u8,
+    ) {
+        let pts = asteroid_outline(a);
+        let (sw, sh) = (sw as f32, sh as f32);
+        for oy in [-sh, 0.0, sh] {
+            for ox in [-sw, 0.0, sw] {
+                let shifted = pts.map(|(x, y)| (x + ox, y + oy));
+                self.fill_polygon(&shifted, FillRule::NonZero, r, g, b);
+            }
+        }
+    }
+
+    /// Fill a polygon given by its vertices in order; it may be concave or self-intersecting,
+    /// with `rule` deciding which regions count as inside. Pixels are filled if their center
+    /// lies inside, so adjacent polygons sharing an edge neither overlap nor leave gaps.
+    pub fn fill_polygon(&mut self, pts: &[(f32, f32)], rule: FillRule, r: u8, g: u8, b: u8) {
+        if pts.len() < 3 {
+            return;
+        }
+        let (min_y, max_y) = pts.iter().fold((f32::MAX, f32::MIN), |(lo, hi), &(_, y)| {
+            (lo.min(y), hi.max(y))
+        });
+        let (min_x, max_x) = pts.iter().fold((f32::MAX, f32::MIN), |(lo, hi), &(x, _)| {
+            (lo.min(x), hi.max(x))
+        });
+        self.mark_f32(min_x, min_y, max_x, max_y, 0.0);
+        let y0 = ceilf(min_y - 0.5).max(0.0) as usize;
+        let y1 = ceilf(max_y - 0.5).clamp(0.0, self.height as f32) as usize;
+        let packed = self.pack(r, g, b);
+
+        // Edge crossings of the current scanline: x position and winding direction
+        let mut crossings: Vec<(f32, i32)> = Vec::with_capacity(pts.len());
+        for y in y0..y1 {
+            let sy = y as f32 + 0.5;
+            crossings.clear();
+            for (i, &(ax, ay)) in pts.iter().enumerate() {
+                let (bx, by) = pts[(i + 1) % pts.len()];
+                // Half-open in y so that shared vertices are counted once
+                let (dir, top, bottom) = if ay < by { (1, ay, by) } else { (-1, by, ay) };
+                if ay == by || sy < top || sy >= bottom {
+                    continue;
+                }
+                let t = (sy - ay) / (by - ay);
+                crossings.push((ax + t * (bx - ax), dir));
+            }
+            crossings.sort_unstable_by(|a, b| a.0.total_cmp(&b.0));
+
+            let mut winding = 0;
+            for pair in crossings.windows(2) {
+                let (xa, dir) = pair[0];
+                winding += dir;
+                let inside = match rule {
+                    FillRule::EvenOdd => winding % 2 != 0,
+                    FillRule::NonZero => winding != 0,
+                };
+                if !inside {
+                    continue;
+                }
+                let x0 = ceilf(xa - 0.5).clamp(0.0, self.width as f32) as usize;
+                let x1 = ceilf(pair[1].0 - 0.5).clamp(0.0, self.width as f32) as usize;
+                let row = y * self.width;
+                self.buf[row + x0..row + x1].fill(packed);
+            }
+        }
+    }
+
+    /// Draw a UI panel: a filled rectangle with a one pixel border.
+    pub fn draw_panel(&mut self, x: isize, y: isize, w: usize, h: usize) {
+        let packed = self.pack(PANEL_FILL.0, PANEL_FILL.1, PANEL_FILL.2);
+        self.mark(x, y, x + w as isize, y + h as isize);
+        self.fill_rect_packed(x, y, w, h, packed);
+        let (x1, y1) = (x + w as isize - 1, y + h as isize - 1);
+        let (r, g, b) = PANEL_BORDER;
+        self.draw_line(x, y, x1, y, r, g, b);
+        self.draw_line(x1, y, x1, y1, r, g, b);
+        self.draw_line(x1, y1, x, y1, r, g, b);
+        self.draw_line(x, y1, x, y, r, g, b);
+    }
+
+    /// Draw particles as single pixels that fade out over their lifetime
+    pub fn draw_particles(&mut self, particles: &[Particle]) {
+        for pt in particles {
+            let (r, g, b) = pt.color;
+            let (x, y) = (floorf(pt.x) as isize, floorf(pt.y) as isize);
+            self.mark(x, y, x + 1, y + 1);
+            self.plot_coverage(x, y, pt.fade(), r, g, b);
+        }
+    }
+
+    /// Draw a projectile as a short streak along its velocity direction
+    #[inline]
+    pub fn draw_projectile(
+        &mut self,
+        x: f32,
+        y: f32,
+        vx: f32,
+        vy: f32,
+        len: f32,
+        style: LineStyle,
+        r: u8,
+        g: u8,
+        b: u8,
+    ) {
+        let vlen = sqrtf(vx * vx + vy * vy);
+        let (tx, ty) = if vlen > 0.0001 {
+            (x - (vx / vlen) * len, y - (vy / vlen) * len)
+        } else {
+            (x, y)
+        };
+        self.draw_line_styled(x, y, tx, ty, style, r, g, b);
+    }
+
+    /// Composite a premultiplied RGBA image over the back buffer ("over" operator), with its
+    /// top-left corner at (`dst_x`, `dst_y`); images may extend past any screen edge.
+    /// `opacity` scales the whole image, e.g. for fades; 255 draws it unchanged.
+    pub fn blit_rgba(
+        &mut self,
+        rgba: &[u8],
+        w: usize,
+        h: usize,
+        dst_x: isize,
+        dst_y: isize,
+        opacity: u8,
+    ) {
+        if opacity == 0 {
+            return;
+        }
+        // Visible source rectangle after clipping against all four edges
+        let sx0 = dst_x.min(0).unsigned_abs().min(w);
+        let sy0 = dst_y.min(0).unsigned_abs().min(h);
+        let sx1 = (self.width as isize - dst_x).clamp(0, w as isize) as usize;
+        let sy1 = (self.height as isize - dst_y).clamp(0, h as isize) as usize;
+        if sx0 >= sx1 {
+            return;
+        }
+        self.mark(dst_x, dst_y, dst_x + w as isize, dst_y + h as isize);
+        for sy in sy0..sy1 {
+            let src_row = &rgba[(sy * w + sx0) * 4..(sy * w + sx1) * 4];
+            let dy = (dst_y + sy as isize) as usize;
+            let dst_off = dy * self.width + (dst_x + sx0 as isize) as usize;
+            for (x, src) in src_row.chunks_exact(4).enumerate() {
+                let [r, g, b, a] = [src[0], src[1], src[2], src[3]];
+                self.blend(dst_off + x, r, g, b, a, opacity);
+            }
+        }
+    }
+
+    /// Draw a premultiplied RGBA image centered at (`x`, `y`), rotated clockwise by `angle`
+    /// radians and scaled by `scale`. Pixels are sampled at their centers, so the sprite may
+    /// be placed at sub-pixel positions and extend past any screen edge.
+    pub fn draw_sprite(
+        &mut self,
+        image: &Image,
+        x: f32,
+        y: f32,
+        angle: f32,
+        scale: f32,
+        sampling: Sampling,
+        opacity: u8,
+    ) {
+        if opacity == 0 || scale <= 0.0 || image.width == 0 || image.height == 0 {
+            return;
+        }
+        let (sin, cos) = (sinf(angle), cosf(angle));
+        let (w, h) = (image.width as f32, image.height as f32);
+
+        // Screen-space bounding box of the rotated, scaled image, clipped to the buffer
+        let half_w = 0.5 * scale * (fabsf(cos) * w + fabsf(sin) * h);
+        let half_h = 0.5 * scale * (fabsf(sin) * w + fabsf(cos) * h);
+        let x0 = floorf(x - half_w).max(0.0) as usize;
+        let y0 = floorf(y - half_h).max(0.0) as usize;
+        let x1 = ceilf(x + half_w).clamp(0.0, self.width as f32) as usize;
+        let y1 = ceilf(y + half_h).clamp(0.0, self.height as f32) as usize;
+        self.mark(x0 as isize, y0 as isize, x1 as isize, y1 as isize);
+
+        let inv_scale = 1.0 / scale;
+        for py in y0..y1 {
+            for px in x0..x1 {
+                // Map the pixel center back into image space (inverse rotation and scale)
+                let dx = px as f32 + 0.5 - x;
+                let dy = py as f32 + 0.5 - y;
+                let u = (cos * dx + sin * dy) * inv_scale + 0.5 * w;
+                let v = (cos * dy - sin * dx) * inv_scale + 0.5 * h;
+                let [r, g, b, a] = match sampling {
+                    Sampling::Nearest => image.texel(floorf(u) as isize, floorf(v) as isize),
+                    Sampling::Bilinear => image.sample_bilinear(u, v),
+                };
+                self.blend(py * self.width + px, r, g, b, a, opacity);
+            }
+        }
+    }
+
+    /// Draw a sprite like [`Self::draw_sprite`], repeated across the edges of an
+    /// `sw` x `sh` wrapping world so that objects crossing an edge appear on both sides.
+    pub fn draw_sprite_wrapped(
+        &mut self,
+        image: &Image,
+        x: f32,
+        y: f32,
+        angle: f32,
+        scale: f32,
+        sampling: Sampling,
+        opacity: u8,
+        sw: usize,
+        sh: usize,
+    ) {
+        let (sw, sh) = (sw as f32, sh as f32);
+        let radius = 0.5 * scale * sqrtf((image.width.pow(2) + image.height.pow(2)) as f32);
+        for oy in [-sh, 0.0, sh] {
+            for ox in [-sw, 0.0, sw] {
+                let (cx, cy) = (x + ox, y + oy);
+                let outside = cx + radius < 0.0
+                    || cy + radius < 0.0
+                    || cx - radius > self.width as f32
+                    || cy - radius > self.height as f32;
+                if !outside {
+                    self.draw_sprite(image, cx, cy, angle, scale, sampling, opacity);
+                }
+            }
+        }
+    }
+
+    /// Blend a premultiplied color scaled by `opacity` over the pixel at index `p`.
+    #[inline(always)]
+    fn blend(&mut self, p: usize, r: u8, g: u8, b: u8, a: u8, opacity: u8) {
+        let (r, g, b, a) = if opacity == 255 {
+            (r, g, b, a)
+        } else {
+            (
+                mul_div255(r, opacity),
+                mul_div255(g, opacity),
+                mul_div255(b, opacity),
+                mul_div255(a, opacity),
+            )
+        };
+        let (r, g, b) = match a {
+            0 => return,
+            255 => (r, g, b),
+            _ => {
+                // dst' = src + dst * (1 - src_alpha), all channels premultiplied
+                let (dr, dg, db) = self.read_rgb(p);
+                let inv = 255 - a;
+                (
+                    r.saturating_add(mul_div255(dr, inv)),
+                    g.saturating_add(mul_div255(dg, inv)),
+                    b.saturating_add(mul_div255(db, inv)),
+                )
+            }
+        };
+        self.buf[p] = self.pack(r, g, b);
+    }
+
+    /// Copy the backbuffer to a linear framebuffer in the target pixel format with `dst_pitch`
+    /// bytes per scanline, e.g. the GOP framebuffer or a plain memory surface. Only the
+    /// [`Self::dirty_region`] is copied; the rest of `dst` is expected to hold the previous frame.
+    /// 32 bpp rows are copied with [`Self::flush_path`].
+    pub fn flush_to(&self, dst: &mut [u8], dst_pitch: usize) {
+        let bpp = self.format.bytes_per_pixel();
+        let row_len = self.width * bpp;
+        assert!(dst_pitch >= row_len, "destination pitch too small");
+        let rows = core::cmp::min(self.height, (dst.len() + dst_pitch - row_len) / dst_pitch);
+
+        for rect in self.dirty_region().rects() {
+            let (y0, y1) = (rect.y0.min(rows), rect.y1.min(rows));
+            if bpp == 4 && dst_pitch == row_len && rect.width() == self.width {
+                // Fast path: contiguous copy of full-width rows
+                let src = &self.buf[y0 * self.width..y1 * self.width];
+                self.flush_path
+                    .copy_row(&mut dst[y0 * dst_pitch..y1 * dst_pitch], src);
+            } else if bpp == 4 {
+                // Copy the rectangle row by row; the destination stride may differ from width
+                for y in y0..y1 {
+                    let src = &self.buf[y * self.width + rect.x0..][..rect.width()];
+                    let dst_row = &mut dst[y * dst_pitch + rect.x0 * 4..][..rect.width() * 4];
+                    self.flush_path.copy_row(dst_row, src);
+                }
+            } else {
+                // Narrow pixels (e.g. 16 bpp bitmask modes): keep the low bytes of each packed pixel
+                for y in y0..y1 {
+                    let src = &self.buf[y * self.width + rect.x0..][..rect.width()];
+                    let dst_row = &mut dst[y * dst_pitch + rect.x0 * bpp..][..rect.width() * bpp];
+                    for (s, d) in src.iter().zip(dst_row.chunks_exact_mut(bpp)) {
+                        d.copy_from_slice(&s.to_le_bytes()[..bpp]);
+                    }
+                }
+            }
+        }
+        self.flush_path.finish();
+    }
+
+    /// Draw text using the embedded bitmap font. `(x, y)` is the top anchor of the first line,
+    /// interpreted according to `align`; each glyph pixel becomes a `scale`×`scale` block.
+    /// Lines are separated by `\n` and aligned individually.
+    pub fn draw_text(
+        &mut self,
+        text: &str,
+        x: isize,
+        y: isize,
+        scale: usize,
+        align: TextAlign,
+        r: u8,
+        g: u8,
+        b: u8,
+    ) {
+        let scale = scale.max(1);
+        let packed = self.pack(r, g, b);
+        let line_h = (FONT_GLYPH_H * scale) as isize;
+        for (li, line) in text.lines().enumerate() {
+            let w = (line.chars().count() * FONT_GLYPH_W * scale) as isize;
+            let mut gx = match align {
+                TextAlign::Left => x,
+                TextAlign::Center => x - w / 2,
+                TextAlign::Right => x - w,
+            };
+            let gy = y + li as isize * line_h;
+            self.mark(gx, gy, gx + w, gy + line_h);
+            for c in line.chars() {
+                for (row, bits) in font::glyph(c).iter().enumerate() {
+                    for col in 0..FONT_GLYPH_W {
+                        if bits & (0x80 >> col) == 0 {
+                            continue;
+                        }
+                        let px = gx + (col * scale) as isize;
+                        let py = gy + (row * scale) as isize;
+                        self.fill_rect_packed(px, py, scale, scale, packed);
+                    }
+                }
+                gx += (FONT_GLYPH_W * scale) as isize;
+            }
+        }
+    }
+
+    /// Fill a clipped rectangle with an already packed pixel value.
+    fn fill_rect_packed(&mut self, x: isize, y: isize, w: usize, h: usize, packed: u32) {
+        let x0 = x.clamp(0, self.width as isize) as usize;
+        let y0 = y.clamp(0, self.height as isize) as usize;
+        let x1 = (x + w as isize).clamp(0, self.width as isize) as usize;
+        let y1 = (y + h as isize).clamp(0, self.height as isize) as usize;
+        if x0 >= x1 {
+            return;
+        }
+        for yy in y0..y1 {
+            let row = yy * self.width;
+            self.buf[row + x0..row + x1].fill(packed);
+        }
+    }
+
+    /// Draw the heads-up display in the top-right corner: score, remaining lives
+    /// (as small ship icons) and the current wave.
+    pub fn draw_hud(&mut self, score: u32, lives: u32, wave: u32) {
+        let (r, g, b) = (220, 220, 220);
+        let right = self.width as isize - HUD_MARGIN;
+        let line_h = (FONT_GLYPH_H * HUD_TEXT_SCALE) as isize;
+        let mut y = HUD_MARGIN;
+
+        let mut line = alloc::string::String::new();
+        let _ = write!(line, "SCORE {score}");
+        self.draw_text(&line, right, y, HUD_TEXT_SCALE, TextAlign::Right, r, g, b);
+        y += line_h + HUD_MARGIN;
+
+        // Lives: one small upward-pointing ship per remaining life
+        let icon_w = (FONT_GLYPH_W * HUD_TEXT_SCALE) as isize;
+        for i in 0..lives as isize {
+            let x = right - icon_w - i * (icon_w + HUD_MARGIN / 2);
+            self.draw_triangle_wire(
+                x + icon_w / 2,
+                y,
+                x,
+                y + line_h,
+                x + icon_w,
+                y + line_h,
+                92,
+                127,
+                255,
+            );
+        }
+        y += line_h + HUD_MARGIN;
+
+        line.clear();
+        let _ = write!(line, "WAVE {wave}");
+        self.draw_text(&line, right, y, HUD_TEXT_SCALE, TextAlign::Right, r, g, b);
+    }
+
+    /// Draw the frame-time overlay in the bottom-left corner: the smoothed frame time and the
+    /// flush time of every path, marking the one in use.
+    pub fn draw_frame_stats(&mut self, stats: &FrameStats) {
+        let (r, g, b) = (140, 255, 140);
+        let line_h = (FONT_GLYPH_H * HUD_TEXT_SCALE) as isize;
+        let rows = 1 + FlushPath::ALL.len() as isize;
+        let mut y = self.height as isize - HUD_MARGIN - rows * (line_h + HUD_MARGIN / 2);
+
+        let mut line = alloc::string::String::new();
+        let _ = write!(line, "FRAME {:6.2} MS", stats.frame_ms);
+        self.draw_text(
+            &line,
+            HUD_MARGIN,
+            y,
+            HUD_TEXT_SCALE,
+            TextAlign::Left,
+            r,
+            g,
+            b,
+        );
+        for path in FlushPath::ALL {
+            y += line_h + HUD_MARGIN / 2;
+            line.clear();
+            let marker = if path == self.flush_path { '>' } else { ' ' };
+            let _ = write!(line, "{marker}{:<8}", path.label());
+            match stats.flush_us(path) {
+                _ if !path.is_supported() => line.push_str("     N/A"),
+                Some(us) => {
+                    let _ = write!(line, "{us:5.0} US");
+                }
+                None => line.push_str("      --"),
+            }
+            self.draw_text(
+                &line,
+                HUD_MARGIN,
+                y,
+                HUD_TEXT_SCALE,
+                TextAlign::Left,
+                r,
+                g,
+                b,
+            );
+        }
+    }
+
+    /// Draw a full-screen menu: a centered title, a vertical list of items with the selected
+    /// one highlighted, and a footer line (e.g. key hints). Long lists scroll to keep the
+    /// selection visible.
+    pub fn draw_menu<S: AsRef<str>>(
+        &mut self,
+        title: &str,
+        items: &[S],
+        selected: usize,
+        footer: &str,
+    ) {
+        let cx = self.width as isize / 2;
+        let layout = self.menu_layout(items.len(), selected);
+        self.draw_text(
+            title,
+            cx,
+            layout.title_y,
+            MENU_TITLE_SCALE,
+            TextAlign::Center,
+            255,
+            255,
+            255,
+        );
+
+        let mut line = alloc::string::String::new();
+        for (row, (i, item)) in items
+            .iter()
+            .enumerate()
+            .skip(layout.first)
+            .take(layout.visible)
+            .enumerate()
+        {
+            let y = layout.list_top + row as isize * layout.row_h;
+            if i == selected {
+                line.clear();
+                let _ = write!(line, "> {} <", item.as_ref());
+                self.draw_text(
+                    &line,
+                    cx,
+                    y,
+                    MENU_ITEM_SCALE,
+                    TextAlign::Center,
+                    255,
+                    255,
+                    0,
+                );
+            } else {
+                self.draw_text(
+                    item.as_ref(),
+                    cx,
+                    y,
+                    MENU_ITEM_SCALE,
+                    TextAlign::Center,
+                    180,
+                    180,
+                    180,
+                );
+            }
+        }
+
+        self.draw_text(
+            footer,
+            cx,
+            layout.footer_y,
+            1,
+            TextAlign::Center,
+            140,
+            140,
+            140,
+        );
+    }
+
+    /// The item of a menu drawn by [`draw_menu`](Self::draw_menu) whose row contains the
+    /// screen row `y`, e.g. the one under the mouse pointer.
+    pub fn menu_item_at(&self, len: usize, selected: usize, y: isize) -> Option<usize> {
+        let layout = self.menu_layout(len, selected);
+        let row = (y - layout.list_top).div_euclid(layout.row_h);
+        let i = layout.first + usize::try_from(row).ok()?;
+        (y >= layout.list_top && (row as usize) < layout.visible && i < len).then_some(i)
+    }
+
+    fn menu_layout(&self, len: usize, selected: usize) -> MenuLayout {
+        let title_y = self.height as isize / 8;
+        let row_h = (FONT_GLYPH_H * MENU_ITEM_SCALE) as isize + MENU_ROW_GAP;
+        let list_top = title_y + (FONT_GLYPH_H * MENU_TITLE_SCALE) as isize + 2 * row_h;
+        let footer_y = self.height as isize - HUD_MARGIN - row_h;
+        let visible = ((footer_y - row_h - list_top) / row_h).max(1) as usize;
+        let first = selected
+            .saturating_sub(visible / 2)
+            .min(len.saturating_sub(visible));
+        MenuLayout {
+            title_y,
+            list_top,
+            row_h,
+            footer_y,
+            first,
+            visible,
+        }
+    }
+
+    /// Draw the mouse pointer as a small crosshair centered at (`x`, `y`).
+    pub fn draw_cursor(&mut self, x: isize, y: isize) {
+        const ARM: isize = 6;
+        self.draw_line(x - ARM, y, x - 2, y, 255, 255, 255);
+        self.draw_line(x + 2, y, x + ARM, y, 255, 255, 255);
+        self.draw_line(x, y - ARM, x, y - 2, 255, 255, 255);
+        self.draw_line(x, y + 2, x, y + ARM, 255, 255, 255);
+    }
+
+    /// Draw the high score table horizontally centered, starting at `y`. The entry at
+    /// `highlight` (e.g. the one just added) is drawn in a different color.
+    pub fn draw_high_scores(&mut self, table: &HighScoreTable, highlight: Option<usize>, y: isize) {
+        let cx = self.width as isize / 2;
+        let row_h = (FONT_GLYPH_H * MENU_ITEM_SCALE) as isize + MENU_ROW_GAP;
+
+        // Wide enough for the longest row, e.g. `10. ABC 1234567  W12`
+        let rows = table.entries().len().max(1) as isize + 2;
+        let panel_w = (24 * FONT_GLYPH_W * MENU_ITEM_SCALE) as isize;
+        self.draw_panel(
+            cx - panel_w / 2,
+            y - PANEL_PADDING,
+            panel_w as usize,
+            (rows * row_h - MENU_ROW_GAP + 2 * PANEL_PADDING) as usize,
+        );
+        self.draw_text(
+            "HIGH SCORES",
+            cx,
+            y,
+            MENU_ITEM_SCALE,
+            TextAlign::Center,
+            255,
+            255,
+            255,
+        );
+        if table.entries().is_empty() {
+            self.draw_text(
+                "NO SCORES YET",
+                cx,
+                y + 2 * row_h,
+                MENU_ITEM_SCALE,
+                TextAlign::Center,
+                180,
+                180,
+                180,
+            );
+            return;
+        }
+
+        let mut line = alloc::string::String::new();
+        for (i, e) in table.entries().iter().enumerate() {
+            line.clear();
+            let initials = core::str::from_utf8(&e.initials).unwrap_or("???");
+            let _ = write!(
+                line,
+                "{:>2}. {initials} {:>7}  W{:<2}",
+                i + 1,
+                e.score,
+                e.wave
+            );
+            let (r, g, b) = if highlight == Some(i) {
+                (255, 255, 0)
+            } else {
+                (180, 180, 180)
+            };
+            let row_y = y + (i as isize + 2) * row_h;
+            self.draw_text(
+                &line,
+                cx,
+                row_y,
+                MENU_ITEM_SCALE,
+                TextAlign::Center,
+                r,
+                g,
+                b,
+            );
+        }
+    }
+
+    /// Draw the initials-entry screen for a new high score, centered at `y`; the letter
+    /// under the cursor is highlighted.
+    pub fn draw_initials_entry(&mut self, entry: &InitialsEntry, score: u32, y: isize) {
+        let cx = self.width as isize / 2;
+        let row_h = (FONT_GLYPH_H * MENU_ITEM_SCALE) as isize + MENU_ROW_GAP;
+
+        // Title, letters with underline and key hints
+        let panel_w = (28 * FONT_GLYPH_W * MENU_ITEM_SCALE) as isize;
+        let panel_h = 4 * row_h + ((INITIALS_SCALE + 1) * FONT_GLYPH_H) as isize + 4;
+        self.draw_panel(
+            cx - panel_w / 2,
+            y - PANEL_PADDING,
+            panel_w as usize,
+            (panel_h + 2 * PANEL_PADDING) as usize,
+        );
+        let mut line = alloc::string::String::new();
+        let _ = write!(line, "NEW HIGH SCORE: {score}");
+        self.draw_text(
+            &line,
+            cx,
+            y,
+            MENU_ITEM_SCALE,
+            TextAlign::Center,
+            255,
+            255,
+            255,
+        );
+
+        let cell_w = ((FONT_GLYPH_W + 2) * INITIALS_SCALE) as isize;
+        let letters_y = y + 2 * row_h;
+        let left = cx - cell_w * entry.initials.len() as isize / 2;
+        for (i, &c) in entry.initials.iter().enumerate() {
+            let (r, g, b) = if i == entry.cursor {
+                (255, 255, 0)
+            } else {
+                (180, 180, 180)
+            };
+            let x = left + i as isize * cell_w + cell_w / 2;
+            let mut buf = [0u8; 4];
+            let letter = (c as char).encode_utf8(&mut buf);
+            self.draw_text(
+                letter,
+                x,
+                letters_y,
+                INITIALS_SCALE,
+                TextAlign::Center,
+                r,
+                g,
+                b,
+            );
+        }
+
+        // Underline the letter being edited
+        let underline_y = letters_y + (FONT_GLYPH_H * INITIALS_SCALE) as isize + 4;
+        let x = left + entry.cursor as isize * cell_w + INITIALS_SCALE as isize;
+        self.draw_line(
+            x,
+            underline_y,
+            x + cell_w - 2 * INITIALS_SCALE as isize,
+            underline_y,
+            255,
+            255,
+            0,
+        );
+
+        self.draw_text(
+            "TYPE OR UP/DOWN  LEFT/RIGHT MOVE  ENTER OK",
+            cx,
+            underline_y + 2 * row_h,
+            1,
+            TextAlign::Center,
+            140,
+            140,
+            140,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RGB565: PixelFormat = PixelFormat::Bitmask {
+        red: 0xF800,
+        green: 0x07E0,
+        blue: 0x001F,
+        reserved: 0,
+    };
+
+    #[test]
+    fn bytes_per_pixel_follows_masks() {
+        assert_eq!(PixelFormat::Rgb.bytes_per_pixel(), 4);
+        assert_eq!(PixelFormat::Bgr.bytes_per_pixel(), 4);
+        assert_eq!(RGB565.bytes_per_pixel(), 2);
+        let bgr24 = PixelFormat::Bitmask {
+            red: 0xFF_0000,
+            green: 0x00_FF00,
+            blue: 0x00_00FF,
+            reserved: 0,
+        };
+        assert_eq!(bgr24.bytes_per_pixel(), 3);
+    }
+
+    #[test]
+    fn bitmask_packing_matches_byte_order_formats() {
+        let (r, g, b, x) = PixelFormat::Bgr.masks();
+        let as_bitmask = PixelFormat::Bitmask {
+            red: r,
+            green: g,
+            blue: b,
+            reserved: x,
+        };
+        let mut a = BackBuffer::new(1, 1, PixelFormat::Bgr);
+        let mut m = BackBuffer::new(1, 1, as_bitmask);
+        a.put_pixel(0, 0, 12, 34, 56);
+        m.put_pixel(0, 0, 12, 34, 56);
+        assert_eq!(a.pixels(), m.pixels());
+        assert_eq!(a.pixels(), &[56, 34, 12, 0]);
+    }
+
+    #[test]
+    fn rgb565_packs_and_flushes_two_bytes_per_pixel() {
+        let mut back = BackBuffer::new(2, 1, RGB565);
+        back.put_pixel(0, 0, 255, 0, 0);
+        back.put_pixel(1, 0, 0, 255, 255);
+        assert_eq!(back.get_pixel(0, 0), (255, 0, 0));
+        assert_eq!(back.get_pixel(1, 0), (0, 255, 255));
+
+        let mut dst = [0xAAu8; 6];
+        back.flush_to(&mut dst, 6);
+        assert_eq!(dst, [0x00, 0xF8, 0xFF, 0x07, 0xAA, 0xAA]);
+    }
+
+    #[test]
+    fn flush_stops_at_the_end_of_a_short_destination() {
+        let mut back = BackBuffer::new(2, 3, PixelFormat::Rgb);
+        back.clear_rgb(1, 2, 3);
+        // Room for two full rows only
+        let mut dst = [0u8; 8 * 2 + 4];
+        back.flush_to(&mut dst, 8);
+        assert_eq!(&dst[8..12], &[1, 2, 3, 0]);
+        assert_eq!(&dst[16..20], &[0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn blit_composites_premultiplied_pixels_over_the_background() {
+        let mut back = BackBuffer::new(3, 1, PixelFormat::Bgr);
+        back.clear_rgb(0, 0, 200);
+        // Opaque, half-transparent white and fully transparent pixels
+        let sprite = [255, 0, 0, 255, 128, 128, 128, 128, 90, 90, 90, 0];
+        back.blit_rgba(&sprite, 3, 1, 0, 0, 255);
+        assert_eq!(back.get_pixel(0, 0), (255, 0, 0));
+        assert_eq!(back.get_pixel(1, 0), (128, 128, 128 + 100));
+        assert_eq!(back.get_pixel(2, 0), (0, 0, 200));
+    }
+
+    #[test]
+    fn blit_opacity_scales_the_whole_image() {
+        let mut back = BackBuffer::new(1, 1, PixelFormat::Rgb);
+        back.clear_rgb(100, 100, 100);
+        back.blit_rgba(&[255, 255, 255, 255], 1, 1, 0, 0, 64);
+        assert_eq!(back.get_pixel(0, 0), (64 + 75, 64 + 75, 64 + 75));
+        back.blit_rgba(&[255, 255, 255, 255], 1, 1, 0, 0, 0);
+        assert_eq!(back.get_pixel(0, 0), (139, 139, 139));
+    }
+
+    #[test]
+    fn blit_clipped_at_the_right_edge_keeps_the_source_stride() {
+        let mut back = BackBuffer::new(3, 2, PixelFormat::Rgb);
+        // 2x2 image placed so that only its left column is visible
+        let sprite = [10, 0, 0, 255, 20, 0, 0, 255, 30, 0, 0, 255, 40, 0, 0, 255];
+        back.blit_rgba(&sprite, 2, 2, 2, 0, 255);
+        assert_eq!(back.get_pixel(2, 0), (10, 0, 0));
+        assert_eq!(back.get_pixel(2, 1), (30, 0, 0));
+    }
+
+    #[test]
+    fn blit_clips_at_negative_positions() {
+        let mut back = BackBuffer::new(2, 2, PixelFormat::Rgb);
+        let sprite = [10, 0, 0, 255, 20, 0, 0, 255, 30, 0, 0, 255, 40, 0, 0, 255];
+        back.blit_rgba(&sprite, 2, 2, -1, -1, 255);
+        assert_eq!(back.get_pixel(0, 0), (40, 0, 0));
+        assert_eq!(back.get_pixel(1, 0), (0, 0, 0));
+        back.blit_rgba(&sprite, 2, 2, -5, 0, 255);
+        assert_eq!(back.get_pixel(0, 1), (0, 0, 0));
+    }
+
+    fn gradient(width: usize, height: usize) -> Image {
+        let rgba = (0..width * height)
+            .flat_map(|i| [(i * 10 + 10) as u8, 0, 0, 255])
+            .collect();
+        Image {
+            width,
+            height,
+            rgba,
+        }
+    }
+
+    #[test]
+    fn unrotated_sprite_matches_blit() {
+        let image = gradient(3, 2);
+        let mut blit = BackBuffer::new(8, 8, PixelFormat::Rgb);
+        blit.blit_rgba(&image.rgba, 3, 2, 2, 3, 255);
+        for sampling in [Sampling::Nearest, Sampling::Bilinear] {
+            let mut sprite = BackBuffer::new(8, 8, PixelFormat::Rgb);
+            sprite.draw_sprite(&image, 3.5, 4.0, 0.0, 1.0, sampling, 255);
+            assert_eq!(sprite.pixels(), blit.pixels(), "{sampling:?}");
+        }
+    }
+
+    #[test]
+    fn sprite_rotates_clockwise() {
+        // A 2x1 image rotated by 90 degrees becomes 1x2 with its left texel on top
+        let image = gradient(2, 1);
+        let mut back = BackBuffer::new(4, 4, PixelFormat::Rgb);
+        let quarter = core::f32::consts::FRAC_PI_2;
+        back.draw_sprite(&image, 2.5, 2.0, quarter, 1.0, Sampling::Nearest, 255);
+        assert_eq!(back.get_pixel(2, 1), (10, 0, 0));
+        assert_eq!(back.get_pixel(2, 2), (20, 0, 0));
+        assert_eq!(back.get_pixel(1, 1), (0, 0, 0));
+        assert_eq!(back.get_pixel(3, 1), (0, 0, 0));
+    }
+
+    #[test]
+    fn polygon_fill_covers_pixel_centers_inside() {
+        let mut back = BackBuffer::new(6, 6, PixelFormat::Rgb);
+        // Square from (1, 1) to (4, 3): pixels x 1..4, y 1..3
+        let square = [(1.0, 1.0), (4.0, 1.0), (4.0, 3.0), (1.0, 3.0)];
+        back.fill_polygon(&square, FillRule::EvenOdd, 255, 255, 255);
+        for y in 0..6 {
+            for x in 0..6 {
+                let inside = (1..4).contains(&x) && (1..3).contains(&y);
+                let expected = if inside { (255, 255, 255) } else { (0, 0, 0) };
+                assert_eq!(back.get_pixel(x, y), expected, "pixel ({x}, {y})");
+            }
+        }
+    }
+
+    #[test]
+    fn fill_rules_differ_for_self_intersecting_polygons() {
+        // Pentagram: the center is wound twice
+        let star: Vec<(f32, f32)> = (0..5)
+            .map(|i| {
+                let t = i as f32 * 4.0 * core::f32::consts::PI / 5.0;
+                (10.0 + 9.0 * sinf(t), 10.0 - 9.0 * cosf(t))
+            })
+            .collect();
+        let mut even_odd = BackBuffer::new(20, 20, PixelFormat::Rgb);
+        even_odd.fill_polygon(&star, FillRule::EvenOdd, 255, 0, 0);
+        let mut non_zero = BackBuffer::new(20, 20, PixelFormat::Rgb);
+        non_zero.fill_polygon(&star, FillRule::NonZero, 255, 0, 0);
+
+        assert_eq!(even_odd.get_pixel(10, 10), (0, 0, 0));
+        assert_eq!(non_zero.get_pixel(10, 10), (255, 0, 0));
+        // A star tip is covered once under both rules
+        assert_eq!(even_odd.get_pixel(10, 3), (255, 0, 0));
+        assert_eq!(non_zero.get_pixel(10, 3), (255, 0, 0));
+    }
+
+    #[test]
+    fn smooth_lines_through_pixel_centers_are_solid_between_the_ends() {
+        let mut back = BackBuffer::new(8, 3, PixelFormat::Rgb);
+        back.draw_line_styled(1.5, 1.5, 6.5, 1.5, LineStyle::smooth(1.0), 200, 100, 50);
+        for x in 2..=5 {
+            assert_eq!(back.get_pixel(x, 1), (200, 100, 50), "pixel ({x}, 1)");
+            assert_eq!(back.get_pixel(x, 0), (0, 0, 0));
+            assert_eq!(back.get_pixel(x, 2), (0, 0, 0));
+        }
+        // The line ends at the centers of its end pixels, covering half of each
+        assert_eq!(back.get_pixel(1, 1), (100, 50, 25));
+        assert_eq!(back.get_pixel(6, 1), (100, 50, 25));
+    }
+
+    #[test]
+    fn smooth_lines_between_pixels_split_their_coverage() {
+        let mut back = BackBuffer::new(8, 4, PixelFormat::Rgb);
+        back.draw_line_styled(1.5, 2.0, 6.5, 2.0, LineStyle::smooth(1.0), 200, 200, 200);
+        assert_eq!(back.get_pixel(3, 1), (100, 100, 100));
+        assert_eq!(back.get_pixel(3, 2), (100, 100, 100));
+    }
+
+    #[test]
+    fn thick_lines_cover_their_width() {
+        let mut back = BackBuffer::new(10, 10, PixelFormat::Rgb);
+        let style = LineStyle {
+            width: 4.0,
+            anti_aliased: false,
+        };
+        back.draw_line_styled(2.0, 5.0, 8.0, 5.0, style, 255, 255, 255);
+        let covered: Vec<usize> = (0..10)
+            .filter(|&y| back.get_pixel(5, y) != (0, 0, 0))
+            .collect();
+        assert_eq!(covered, [3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn particles_fade_with_age() {
+        let mut back = BackBuffer::new(4, 4, PixelFormat::Rgb);
+        let pt = Particle {
+            x: 1.5,
+            y: 2.5,
+            vx: 0.0,
+            vy: 0.0,
+            age: 0.5,
+            lifetime: 1.0,
+            color: (200, 100, 50),
+        };
+        back.draw_particles(&[pt]);
+        assert_eq!(back.get_pixel(1, 2), (100, 50, 25));
+        assert_eq!(back.get_pixel(2, 2), (0, 0, 0));
+    }
+
+    #[test]
+    fn flush_copies_what_changed_since_the_previous_frame() {
+        let mut back = BackBuffer::new(8, 4, PixelFormat::Rgb);
+        let mut dst = vec![0u8; 8 * 4 * 4];
+        back.clear_rgb(0, 0, 0);
+        back.put_pixel(2, 1, 255, 255, 255);
+        back.flush_to(&mut dst, 8 * 4);
+
+        back.clear_rgb(0, 0, 0);
+        back.put_pixel(5, 2, 255, 0, 0);
+        let rects: Vec<Rect> = back.dirty_region().rects().to_vec();
+        assert_eq!(rects.len(), 2);
+        // Pixels outside the dirty region are left alone
+        dst[(3 * 8 + 7) * 4] = 0xAA;
+        back.flush_to(&mut dst, 8 * 4);
+        assert_eq!(dst[(3 * 8 + 7) * 4], 0xAA);
+        assert_eq!(&dst[(8 + 2) * 4..][..3], &[0, 0, 0]);
+        assert_eq!(&dst[(2 * 8 + 5) * 4..][..3], &[255, 0, 0]);
+    }
+
+    #[test]
+    fn clearing_with_another_color_touches_every_pixel() {
+        let mut back = BackBuffer::new(4, 4, PixelFormat::Rgb);
+        back.clear_rgb(0, 0, 0);
+        back.clear_rgb(10, 20, 30);
+        assert_eq!(back.get_pixel(3, 3), (10, 20, 30));
+        assert_eq!(
+            back.dirty_region().rects(),
+            &[Rect {
+                x0: 0,
+                y0: 0,
+                x1: 4,
+                y1: 4
+            }]
+        );
+    }
+
+    #[test]
+    fn menu_hit_test_finds_the_drawn_rows() {
+        let mut back = BackBuffer::new(320, 240, PixelFormat::Rgb);
+        let items = ["A", "B", "C", "D", "E", "F", "G", "H", "I", "J"];
+        for selected in [0, 5, 9] {
+            back.clear_rgb(0, 0, 0);
+            back.draw_menu("T", &items, selected, "");
+            // The selected item is the only yellow text
+            let row = (0..back.height)
+                .find(|&y| (0..back.width).any(|x| back.get_pixel(x, y) == (255, 255, 0)))
+                .unwrap();
+            let hit = back.menu_item_at(items.len(), selected, row as isize);
+            assert_eq!(hit, Some(selected));
+        }
+        assert_eq!(back.menu_item_at(items.len(), 0, 0), None);
+        assert_eq!(back.menu_item_at(items.len(), 0, 239), None);
+    }
+}
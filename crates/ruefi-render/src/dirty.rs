@@ -0,0 +1,183 @@
+//! Dirty-rectangle tracking, so that clearing and presenting only touch the parts of
+//! the screen that changed between frames.
+
+/// Rectangles tracked per region; beyond that, the closest rectangles are merged.
+pub const MAX_DIRTY_RECTS: usize = 32;
+
+/// A screen rectangle; `x1` and `y1` are exclusive.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Rect {
+    pub x0: usize,
+    pub y0: usize,
+    pub x1: usize,
+    pub y1: usize,
+}
+
+impl Rect {
+    /// The rectangle from (`x0`, `y0`) to (`x1`, `y1`) clipped to a `width` x `height` screen;
+    /// `None` if nothing of it is visible.
+    pub fn clipped(
+        x0: isize,
+        y0: isize,
+        x1: isize,
+        y1: isize,
+        width: usize,
+        height: usize,
+    ) -> Option<Self> {
+        let rect = Self {
+            x0: x0.clamp(0, width as isize) as usize,
+            y0: y0.clamp(0, height as isize) as usize,
+            x1: x1.clamp(0, width as isize) as usize,
+            y1: y1.clamp(0, height as isize) as usize,
+        };
+        (rect.x0 < rect.x1 && rect.y0 < rect.y1).then_some(rect)
+    }
+
+    #[inline]
+    pub const fn width(&self) -> usize {
+        self.x1 - self.x0
+    }
+
+    #[inline]
+    pub const fn height(&self) -> usize {
+        self.y1 - self.y0
+    }
+
+    #[inline]
+    pub const fn area(&self) -> usize {
+        self.width() * self.height()
+    }
+
+    /// The smallest rectangle containing both.
+    pub fn union(&self, other: &Self) -> Self {
+        Self {
+            x0: self.x0.min(other.x0),
+            y0: self.y0.min(other.y0),
+            x1: self.x1.max(other.x1),
+            y1: self.y1.max(other.y1),
+        }
+    }
+
+    /// Whether the rectangles overlap or share an edge.
+    pub const fn touches(&self, other: &Self) -> bool {
+        self.x0 <= other.x1 && other.x0 <= self.x1 && self.y0 <= other.y1 && other.y0 <= self.y1
+    }
+}
+
+/// A set of non-touching screen rectangles, kept in a fixed-size array.
+#[derive(Clone, Copy, Debug)]
+pub struct DirtyRegion {
+    rects: [Rect; MAX_DIRTY_RECTS],
+    len: usize,
+}
+
+impl Default for DirtyRegion {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DirtyRegion {
+    pub const fn new() -> Self {
+        Self {
+            rects: [Rect {
+                x0: 0,
+                y0: 0,
+                x1: 0,
+                y1: 0,
+            }; MAX_DIRTY_RECTS],
+            len: 0,
+        }
+    }
+
+    #[inline]
+    pub fn rects(&self) -> &[Rect] {
+        &self.rects[..self.len]
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Add a rectangle, merging it with every rectangle it touches. If the region is full,
+    /// it is merged with the rectangle whose bounding box grows the least instead.
+    pub fn add(&mut self, rect: Rect) {
+        let mut rect = rect;
+        let mut i = 0;
+        while i < self.len {
+            if self.rects[i].touches(&rect) {
+                rect = rect.union(&self.remove(i));
+                // The grown rectangle may now touch rectangles checked before
+                i = 0;
+            } else {
+                i += 1;
+            }
+        }
+        if self.len == MAX_DIRTY_RECTS {
+            let growth = |r: &Rect| r.union(&rect).area() - r.area();
+            let closest = (0..self.len)
+                .min_by_key(|&i| growth(&self.rects[i]))
+                .unwrap_or(0);
+            let merged = rect.union(&self.remove(closest));
+            self.add(merged);
+            return;
+        }
+        self.rects[self.len] = rect;
+        self.len += 1;
+    }
+
+    /// Add all rectangles of another region.
+    pub fn extend(&mut self, other: &Self) {
+        for rect in other.rects() {
+            self.add(*rect);
+        }
+    }
+
+    fn remove(&mut self, i: usize) -> Rect {
+        let rect = self.rects[i];
+        self.len -= 1;
+        self.rects[i] = self.rects[self.len];
+        rect
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x0: usize, y0: usize, x1: usize, y1: usize) -> Rect {
+        Rect { x0, y0, x1, y1 }
+    }
+
+    #[test]
+    fn clipping_drops_invisible_rects() {
+        assert_eq!(Rect::clipped(-5, -5, 3, 4, 10, 10), Some(rect(0, 0, 3, 4)));
+        assert_eq!(Rect::clipped(8, 2, 20, 3, 10, 10), Some(rect(8, 2, 10, 3)));
+        assert_eq!(Rect::clipped(12, 2, 20, 3, 10, 10), None);
+        assert_eq!(Rect::clipped(2, 2, 2, 3, 10, 10), None);
+    }
+
+    #[test]
+    fn touching_rects_merge_and_separate_ones_do_not() {
+        let mut region = DirtyRegion::new();
+        region.add(rect(0, 0, 10, 10));
+        region.add(rect(50, 50, 60, 60));
+        assert_eq!(region.rects().len(), 2);
+        // Bridges both rectangles
+        region.add(rect(10, 10, 50, 50));
+        assert_eq!(region.rects(), &[rect(0, 0, 60, 60)]);
+    }
+
+    #[test]
+    fn full_regions_merge_the_closest_rects() {
+        let mut region = DirtyRegion::new();
+        for i in 0..MAX_DIRTY_RECTS {
+            region.add(rect(i * 10, 0, i * 10 + 5, 5));
+        }
+        assert_eq!(region.rects().len(), MAX_DIRTY_RECTS);
+        region.add(rect(0, 8, 5, 10));
+        assert_eq!(region.rects().len(), MAX_DIRTY_RECTS);
+        assert!(region.rects().contains(&rect(0, 0, 5, 10)));
+    }
+}
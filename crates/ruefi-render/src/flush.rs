@@ -0,0 +1,245 @@
+//! Copying back buffer rows to the framebuffer. Besides a plain copy, x86-64 CPUs can use
+//! SSE2 or AVX2 non-temporal stores, which bypass the cache on their way to the (typically
+//! write-combining) framebuffer memory.
+
+#[cfg(target_arch = "x86_64")]
+use core::arch::x86_64::{
+    __cpuid, __cpuid_count, __m128i, __m256i, _mm_loadu_si128, _mm_sfence, _mm_stream_si128,
+    _mm256_loadu_si256, _mm256_stream_si256, _xgetbv,
+};
+#[cfg(target_arch = "x86_64")]
+use core::sync::atomic::{AtomicU8, Ordering};
+
+/// Weight of the newest sample in the smoothed timings of [`FrameStats`].
+const SMOOTHING: f32 = 0.05;
+
+/// How rows of 32 bpp pixels are copied to the framebuffer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FlushPath {
+    /// `copy_from_slice`; whatever the compiler makes of it.
+    Scalar,
+    /// 16-byte SSE2 non-temporal stores.
+    Sse2,
+    /// 32-byte AVX2 non-temporal stores.
+    Avx2,
+}
+
+impl FlushPath {
+    pub const ALL: [FlushPath; 3] = [FlushPath::Scalar, FlushPath::Sse2, FlushPath::Avx2];
+
+    /// The widest path supported by the CPU.
+    pub fn detect() -> Self {
+        Self::ALL
+            .into_iter()
+            .rev()
+            .find(|path| path.is_supported())
+            .unwrap_or(FlushPath::Scalar)
+    }
+
+    /// Whether the CPU (and, for AVX2, the firmware) supports this path.
+    pub fn is_supported(self) -> bool {
+        match self {
+            FlushPath::Scalar => true,
+            #[cfg(target_arch = "x86_64")]
+            FlushPath::Sse2 => cpu_features() & FEATURE_SSE2 != 0,
+            #[cfg(target_arch = "x86_64")]
+            FlushPath::Avx2 => cpu_features() & FEATURE_AVX2 != 0,
+            #[cfg(not(target_arch = "x86_64"))]
+            _ => false,
+        }
+    }
+
+    /// Short name for the frame-time overlay.
+    pub const fn label(self) -> &'static str {
+        match self {
+            FlushPath::Scalar => "SCALAR",
+            FlushPath::Sse2 => "SSE2 NT",
+            FlushPath::Avx2 => "AVX2 NT",
+        }
+    }
+
+    /// Copy little-endian pixels to `dst`, which must be exactly as long as `src` in bytes.
+    /// Unsupported paths fall back to a plain copy.
+    pub(crate) fn copy_row(self, dst: &mut [u8], src: &[u32]) {
+        let src = as_bytes(src);
+        match self {
+            // SAFETY: the CPU supports the instructions of the path, as checked first.
+            #[cfg(target_arch = "x86_64")]
+            FlushPath::Sse2 if self.is_supported() => unsafe { copy_row_sse2(dst, src) },
+            #[cfg(target_arch = "x86_64")]
+            FlushPath::Avx2 if self.is_supported() => unsafe { copy_row_avx2(dst, src) },
+            _ => dst.copy_from_slice(src),
+        }
+    }
+
+    /// Wait for all non-temporal stores to reach memory; call once after copying all rows.
+    pub(crate) fn finish(self) {
+        #[cfg(target_arch = "x86_64")]
+        if self != FlushPath::Scalar && self.is_supported() {
+            // SAFETY: both SIMD paths imply SSE2 support.
+            unsafe { store_fence() }
+        }
+    }
+}
+
+/// View little-endian `u32` pixels as bytes.
+#[inline]
+pub(crate) fn as_bytes(pixels: &[u32]) -> &[u8] {
+    // SAFETY: `u8` has no alignment requirement and every bit pattern is valid; on the
+    // little-endian targets we run on, the bytes are in framebuffer order.
+    unsafe { core::slice::from_raw_parts(pixels.as_ptr().cast::<u8>(), pixels.len() * 4) }
+}
+
+#[cfg(target_arch = "x86_64")]
+const FEATURE_DETECTED: u8 = 1;
+#[cfg(target_arch = "x86_64")]
+const FEATURE_SSE2: u8 = 2;
+#[cfg(target_arch = "x86_64")]
+const FEATURE_AVX2: u8 = 4;
+
+/// CPU features relevant to flushing, queried once via `cpuid`.
+#[cfg(target_arch = "x86_64")]
+fn cpu_features() -> u8 {
+    static FEATURES: AtomicU8 = AtomicU8::new(0);
+    let cached = FEATURES.load(Ordering::Relaxed);
+    if cached != 0 {
+        return cached;
+    }
+
+    let mut features = FEATURE_DETECTED;
+    let max_leaf = __cpuid(0).eax;
+    let leaf1 = __cpuid(1);
+    if leaf1.edx & (1 << 26) != 0 {
+        features |= FEATURE_SSE2;
+    }
+    // AVX needs the firmware to have enabled the YMM register state (OSXSAVE and XCR0 bits 1, 2)
+    let osxsave = leaf1.ecx & (1 << 27) != 0;
+    let avx = leaf1.ecx & (1 << 28) != 0;
+    // SAFETY: `xgetbv` is available when the OS (here: firmware) has set CR4.OSXSAVE.
+    if osxsave && avx && unsafe { xcr0() } & 0b110 == 0b110 && max_leaf >= 7 {
+        let leaf7 = __cpuid_count(7, 0);
+        if leaf7.ebx & (1 << 5) != 0 {
+            features |= FEATURE_AVX2;
+        }
+    }
+    FEATURES.store(features, Ordering::Relaxed);
+    features
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "xsave")]
+unsafe fn xcr0() -> u64 {
+    // SAFETY: the caller checked that `xgetbv` is enabled.
+    unsafe { _xgetbv(0) }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn store_fence() {
+    _mm_sfence();
+}
+
+/// Copy with 16-byte non-temporal stores; the unaligned head and tail are copied plainly.
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn copy_row_sse2(dst: &mut [u8], src: &[u8]) {
+    let head = dst.as_ptr().align_offset(16).min(dst.len());
+    dst[..head].copy_from_slice(&src[..head]);
+    let mut i = head;
+    while i + 16 <= dst.len() {
+        // SAFETY: `dst + i` is 16-byte aligned and 16 bytes of both slices are in bounds.
+        unsafe {
+            let v = _mm_loadu_si128(src.as_ptr().add(i).cast::<__m128i>());
+            _mm_stream_si128(dst.as_mut_ptr().add(i).cast::<__m128i>(), v);
+        }
+        i += 16;
+    }
+    dst[i..].copy_from_slice(&src[i..]);
+}
+
+/// Copy with 32-byte non-temporal stores; the unaligned head and tail are copied plainly.
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn copy_row_avx2(dst: &mut [u8], src: &[u8]) {
+    let head = dst.as_ptr().align_offset(32).min(dst.len());
+    dst[..head].copy_from_slice(&src[..head]);
+    let mut i = head;
+    while i + 32 <= dst.len() {
+        // SAFETY: `dst + i` is 32-byte aligned and 32 bytes of both slices are in bounds.
+        unsafe {
+            let v = _mm256_loadu_si256(src.as_ptr().add(i).cast::<__m256i>());
+            _mm256_stream_si256(dst.as_mut_ptr().add(i).cast::<__m256i>(), v);
+        }
+        i += 32;
+    }
+    dst[i..].copy_from_slice(&src[i..]);
+}
+
+/// Smoothed frame and flush timings for the frame-time overlay.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FrameStats {
+    pub frame_ms: f32,          // time between frames
+    flush_us: [Option<f32>; 3], // time per flush, by path (same order as `FlushPath::ALL`)
+}
+
+impl FrameStats {
+    /// Add the time between two frames.
+    pub fn record_frame(&mut self, seconds: f32) {
+        self.frame_ms = smooth(Some(self.frame_ms), seconds * 1e3);
+    }
+
+    /// Add the time one flush took with the given path.
+    pub fn record_flush(&mut self, path: FlushPath, seconds: f32) {
+        let slot = &mut self.flush_us[path as usize];
+        *slot = Some(smooth(*slot, seconds * 1e6));
+    }
+
+    /// Smoothed flush time of a path in microseconds; `None` until it was measured.
+    pub fn flush_us(&self, path: FlushPath) -> Option<f32> {
+        self.flush_us[path as usize]
+    }
+}
+
+/// Exponential moving average, starting at the first sample.
+fn smooth(average: Option<f32>, sample: f32) -> f32 {
+    match average {
+        Some(avg) if avg > 0.0 => avg + SMOOTHING * (sample - avg),
+        _ => sample,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_path_copies_rows_exactly() {
+        let src: Vec<u32> = (0..301u32).map(|i| i.wrapping_mul(0x0103_0507)).collect();
+        for path in FlushPath::ALL {
+            // Offsets that break the SIMD alignment exercise the plainly copied head and tail
+            for offset in [0, 4, 12] {
+                let mut dst = vec![0u8; src.len() * 4 + 64];
+                path.copy_row(&mut dst[offset..][..src.len() * 4], &src);
+                path.finish();
+                assert_eq!(&dst[offset..][..src.len() * 4], as_bytes(&src), "{path:?}");
+                assert!(dst[..offset].iter().all(|&b| b == 0), "{path:?}");
+                assert!(
+                    dst[offset + src.len() * 4..].iter().all(|&b| b == 0),
+                    "{path:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn stats_start_at_the_first_sample_and_smooth_later_ones() {
+        let mut stats = FrameStats::default();
+        assert_eq!(stats.flush_us(FlushPath::Sse2), None);
+        stats.record_flush(FlushPath::Sse2, 100e-6);
+        assert_eq!(stats.flush_us(FlushPath::Sse2), Some(100.0));
+        stats.record_flush(FlushPath::Sse2, 300e-6);
+        let us = stats.flush_us(FlushPath::Sse2).unwrap();
+        assert!(us > 100.0 && us < 300.0);
+        assert_eq!(stats.flush_us(FlushPath::Scalar), None);
+    }
+}
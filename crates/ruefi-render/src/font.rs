@@ -0,0 +1,19 @@
+//! Embedded bitmap font, generated at build time from `assets/font.png` in the repository root.
+
+mod generated {
+    include!(concat!(env!("OUT_DIR"), "/font_gen.rs"));
+}
+
+pub use generated::{FONT_GLYPH_H, FONT_GLYPH_W};
+
+/// Row bitmaps (bit 7 = leftmost pixel) of the glyph for `c`.
+/// Characters outside the font are rendered as `?`.
+#[inline]
+pub fn glyph(c: char) -> &'static [u8] {
+    let first = generated::FONT_FIRST_CHAR as u32;
+    let idx = match (c as u32).checked_sub(first) {
+        Some(i) if (i as usize) < generated::FONT_GLYPH_COUNT => i as usize,
+        _ => (b'?' as u32 - first) as usize,
+    };
+    &generated::FONT_BITMAP[idx * FONT_GLYPH_H..][..FONT_GLYPH_H]
+}
@@ -0,0 +1,416 @@
+//! Minimal zlib/DEFLATE decompressor (RFC 1950, RFC 1951) for runtime asset loading.
+//!
+//! Favors small code over speed: Huffman codes are decoded bit by bit, which is plenty
+//! for the few kilobytes of image data loaded at startup.
+
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Longest Huffman code in DEFLATE.
+const MAX_BITS: usize = 15;
+
+/// Base lengths and extra bits of length symbols 257..=285.
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+
+/// Base distances and extra bits of distance symbols 0..=29.
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+/// Order in which the code length code lengths of a dynamic block are stored.
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+/// Reasons a zlib stream cannot be decompressed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InflateError {
+    Header,        // not a zlib stream or unsupported options (e.g. preset dictionary)
+    Truncated,     // input ended in the middle of the stream
+    BlockType,     // reserved block type 3
+    StoredLength,  // stored block length does not match its complement
+    HuffmanTable,  // over-subscribed or otherwise invalid code lengths
+    InvalidSymbol, // code not present in the table or symbol out of range
+    Distance,      // back-reference before the start of the output
+    TooLarge,      // output exceeds the caller's limit
+    Checksum,      // Adler-32 of the output does not match
+}
+
+impl fmt::Display for InflateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Header => "invalid zlib header",
+            Self::Truncated => "compressed data is truncated",
+            Self::BlockType => "invalid deflate block type",
+            Self::StoredLength => "invalid stored block length",
+            Self::HuffmanTable => "invalid Huffman table",
+            Self::InvalidSymbol => "invalid Huffman code",
+            Self::Distance => "back-reference out of range",
+            Self::TooLarge => "decompressed data is too large",
+            Self::Checksum => "Adler-32 checksum mismatch",
+        })
+    }
+}
+
+/// Decompress a zlib stream, producing at most `max_len` bytes.
+pub fn zlib_decompress(data: &[u8], max_len: usize) -> Result<Vec<u8>, InflateError> {
+    let [cmf, flg, ..] = *data else {
+        return Err(InflateError::Header);
+    };
+    let preset_dictionary = flg & 0x20 != 0;
+    if cmf & 0x0F != 8 || cmf >> 4 > 7 || (u16::from(cmf) << 8 | u16::from(flg)) % 31 != 0 {
+        return Err(InflateError::Header);
+    }
+    if preset_dictionary {
+        return Err(InflateError::Header);
+    }
+
+    let mut reader = BitReader::new(&data[2..]);
+    let mut out = Vec::new();
+    inflate(&mut reader, &mut out, max_len)?;
+
+    let trailer = reader.remaining_bytes();
+    let expected = trailer
+        .first_chunk::<4>()
+        .map(|b| u32::from_be_bytes(*b))
+        .ok_or(InflateError::Truncated)?;
+    if adler32(&out) != expected {
+        return Err(InflateError::Checksum);
+    }
+    Ok(out)
+}
+
+/// Decompress raw DEFLATE blocks until the final block has been read.
+fn inflate(reader: &mut BitReader, out: &mut Vec<u8>, max_len: usize) -> Result<(), InflateError> {
+    loop {
+        let last = reader.bits(1)? == 1;
+        match reader.bits(2)? {
+            0 => stored_block(reader, out, max_len)?,
+            1 => {
+                let (lit, dist) = fixed_tables();
+                huffman_block(reader, out, max_len, &lit, &dist)?;
+            }
+            2 => {
+                let (lit, dist) = dynamic_tables(reader)?;
+                huffman_block(reader, out, max_len, &lit, &dist)?;
+            }
+            _ => return Err(InflateError::BlockType),
+        }
+        if last {
+            return Ok(());
+        }
+    }
+}
+
+fn stored_block(
+    reader: &mut BitReader,
+    out: &mut Vec<u8>,
+    max_len: usize,
+) -> Result<(), InflateError> {
+    reader.align_to_byte();
+    let len = reader.bits(16)? as u16;
+    let nlen = reader.bits(16)? as u16;
+    if len != !nlen {
+        return Err(InflateError::StoredLength);
+    }
+    let bytes = reader.take_bytes(len as usize)?;
+    if out.len() + bytes.len() > max_len {
+        return Err(InflateError::TooLarge);
+    }
+    out.extend_from_slice(bytes);
+    Ok(())
+}
+
+fn huffman_block(
+    reader: &mut BitReader,
+    out: &mut Vec<u8>,
+    max_len: usize,
+    lit: &Huffman,
+    dist: &Huffman,
+) -> Result<(), InflateError> {
+    loop {
+        let symbol = lit.decode(reader)? as usize;
+        match symbol {
+            0..=255 => {
+                if out.len() >= max_len {
+                    return Err(InflateError::TooLarge);
+                }
+                out.push(symbol as u8);
+            }
+            256 => return Ok(()),
+            257..=285 => {
+                let i = symbol - 257;
+                let len = LENGTH_BASE[i] as usize + reader.bits(LENGTH_EXTRA[i])? as usize;
+                let d = dist.decode(reader)? as usize;
+                if d >= DIST_BASE.len() {
+                    return Err(InflateError::InvalidSymbol);
+                }
+                let distance = DIST_BASE[d] as usize + reader.bits(DIST_EXTRA[d])? as usize;
+                if distance > out.len() {
+                    return Err(InflateError::Distance);
+                }
+                if out.len() + len > max_len {
+                    return Err(InflateError::TooLarge);
+                }
+                // Byte by byte: the source may overlap the bytes being written
+                let start = out.len() - distance;
+                for k in 0..len {
+                    out.push(out[start + k]);
+                }
+            }
+            _ => return Err(InflateError::InvalidSymbol),
+        }
+    }
+}
+
+/// The predefined literal/length and distance codes of block type 1.
+fn fixed_tables() -> (Huffman, Huffman) {
+    let mut lengths = [0u8; 288];
+    lengths[..144].fill(8);
+    lengths[144..256].fill(9);
+    lengths[256..280].fill(7);
+    lengths[280..].fill(8);
+    let lit = Huffman::new(&lengths).expect("fixed literal code is valid");
+    let dist = Huffman::new(&[5; 30]).expect("fixed distance code is valid");
+    (lit, dist)
+}
+
+/// Read the code length code, then the literal/length and distance code lengths.
+fn dynamic_tables(reader: &mut BitReader) -> Result<(Huffman, Huffman), InflateError> {
+    let hlit = reader.bits(5)? as usize + 257;
+    let hdist = reader.bits(5)? as usize + 1;
+    let hclen = reader.bits(4)? as usize + 4;
+    if hlit > 286 || hdist > 30 {
+        return Err(InflateError::HuffmanTable);
+    }
+
+    let mut code_lengths = [0u8; 19];
+    for &i in &CODE_LENGTH_ORDER[..hclen] {
+        code_lengths[i] = reader.bits(3)? as u8;
+    }
+    let code_length_code = Huffman::new(&code_lengths)?;
+
+    let mut lengths = [0u8; 286 + 30];
+    let mut n = 0;
+    while n < hlit + hdist {
+        let symbol = code_length_code.decode(reader)?;
+        let (value, repeat) = match symbol {
+            0..=15 => (symbol as u8, 1),
+            16 => {
+                let previous = *lengths[..n].last().ok_or(InflateError::HuffmanTable)?;
+                (previous, 3 + reader.bits(2)? as usize)
+            }
+            17 => (0, 3 + reader.bits(3)? as usize),
+            _ => (0, 11 + reader.bits(7)? as usize),
+        };
+        if n + repeat > hlit + hdist {
+            return Err(InflateError::HuffmanTable);
+        }
+        lengths[n..n + repeat].fill(value);
+        n += repeat;
+    }
+    // Without an end-of-block code the block could never terminate
+    if lengths[256] == 0 {
+        return Err(InflateError::HuffmanTable);
+    }
+
+    let lit = Huffman::new(&lengths[..hlit])?;
+    let dist = Huffman::new(&lengths[hlit..hlit + hdist])?;
+    Ok((lit, dist))
+}
+
+/// Canonical Huffman code: number of codes per length and symbols in code order.
+struct Huffman {
+    counts: [u16; MAX_BITS + 1],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    /// Build the code from per-symbol code lengths (0 = unused). Incomplete codes are
+    /// accepted, as DEFLATE allows them for single-symbol distance codes.
+    fn new(lengths: &[u8]) -> Result<Self, InflateError> {
+        let mut counts = [0u16; MAX_BITS + 1];
+        for &len in lengths {
+            counts[len as usize] += 1;
+        }
+        counts[0] = 0;
+
+        // Reject over-subscribed codes
+        let mut left: i32 = 1;
+        for &count in &counts[1..] {
+            left = (left << 1) - count as i32;
+            if left < 0 {
+                return Err(InflateError::HuffmanTable);
+            }
+        }
+
+        let mut offsets = [0u16; MAX_BITS + 2];
+        for len in 1..=MAX_BITS {
+            offsets[len + 1] = offsets[len] + counts[len];
+        }
+        let mut symbols = alloc::vec![0u16; offsets[MAX_BITS + 1] as usize];
+        for (symbol, &len) in lengths.iter().enumerate() {
+            if len != 0 {
+                symbols[offsets[len as usize] as usize] = symbol as u16;
+                offsets[len as usize] += 1;
+            }
+        }
+        Ok(Self { counts, symbols })
+    }
+
+    /// Decode one symbol, reading the code bit by bit (codes are stored MSB first).
+    fn decode(&self, reader: &mut BitReader) -> Result<u16, InflateError> {
+        let mut code: i32 = 0; // code bits read so far
+        let mut first: i32 = 0; // first code of the current length
+        let mut index: i32 = 0; // index of the first code of the current length in `symbols`
+        for &count in &self.counts[1..] {
+            code |= reader.bits(1)? as i32;
+            let count = count as i32;
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(InflateError::InvalidSymbol)
+    }
+}
+
+/// Reads DEFLATE's LSB-first bit stream.
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,   // next byte to load
+    bit_buf: u32, // loaded bits not consumed yet, next bit in the LSB
+    bit_count: u8,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            pos: 0,
+            bit_buf: 0,
+            bit_count: 0,
+        }
+    }
+
+    /// Read `n` (at most 16) bits as an integer, first bit in the LSB.
+    fn bits(&mut self, n: u8) -> Result<u32, InflateError> {
+        while self.bit_count < n {
+            let byte = *self.data.get(self.pos).ok_or(InflateError::Truncated)?;
+            self.pos += 1;
+            self.bit_buf |= u32::from(byte) << self.bit_count;
+            self.bit_count += 8;
+        }
+        let value = self.bit_buf & ((1u32 << n) - 1);
+        self.bit_buf >>= n;
+        self.bit_count -= n;
+        Ok(value)
+    }
+
+    /// Drop the bits up to the next byte boundary.
+    fn align_to_byte(&mut self) {
+        let partial = self.bit_count % 8;
+        self.bit_buf >>= partial;
+        self.bit_count -= partial;
+    }
+
+    /// Take whole bytes; only valid at a byte boundary.
+    fn take_bytes(&mut self, len: usize) -> Result<&'a [u8], InflateError> {
+        // Return any buffered whole bytes to the input first
+        self.pos -= (self.bit_count / 8) as usize;
+        self.bit_buf = 0;
+        self.bit_count = 0;
+        let bytes = self
+            .data
+            .get(self.pos..self.pos + len)
+            .ok_or(InflateError::Truncated)?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    /// The input after the last partially consumed byte.
+    fn remaining_bytes(&self) -> &'a [u8] {
+        let pos = self.pos - (self.bit_count / 8) as usize;
+        &self.data[pos..]
+    }
+}
+
+/// Adler-32 checksum of zlib streams.
+fn adler32(data: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    let (mut a, mut b) = (1u32, 0u32);
+    // 5552 is the largest block size for which `b` cannot overflow before the modulo
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += u32::from(byte);
+            b += a;
+        }
+        a %= MOD;
+        b %= MOD;
+    }
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `hello, hello!` as a single stored block.
+    const STORED: [u8; 24] = [
+        0x78, 0x01, 0x01, 0x0D, 0x00, 0xF2, 0xFF, 0x68, 0x65, 0x6C, 0x6C, 0x6F, 0x2C, 0x20, 0x68,
+        0x65, 0x6C, 0x6C, 0x6F, 0x21, 0x21, 0x70, 0x04, 0x96,
+    ];
+
+    /// `hello, hello! hello, hello!` with fixed Huffman codes and back-references.
+    const FIXED: [u8; 22] = [
+        0x78, 0xDA, 0xCB, 0x48, 0xCD, 0xC9, 0xC9, 0xD7, 0x51, 0xC8, 0x00, 0x51, 0x8A, 0x10, 0x0A,
+        0xC6, 0x03, 0x00, 0x84, 0xC7, 0x09, 0x4B,
+    ];
+
+    #[test]
+    fn decodes_stored_blocks() {
+        assert_eq!(zlib_decompress(&STORED, 1024).unwrap(), b"hello, hello!");
+    }
+
+    #[test]
+    fn decodes_fixed_huffman_blocks() {
+        assert_eq!(
+            zlib_decompress(&FIXED, 1024).unwrap(),
+            b"hello, hello! hello, hello!"
+        );
+    }
+
+    #[test]
+    fn rejects_corrupt_streams() {
+        let mut bad_checksum = FIXED;
+        bad_checksum[FIXED.len() - 1] ^= 1;
+        assert_eq!(
+            zlib_decompress(&bad_checksum, 1024),
+            Err(InflateError::Checksum)
+        );
+        assert_eq!(
+            zlib_decompress(&FIXED[..10], 1024),
+            Err(InflateError::Truncated)
+        );
+        assert_eq!(zlib_decompress(&FIXED, 8), Err(InflateError::TooLarge));
+        assert_eq!(zlib_decompress(&[0x78, 0x00], 8), Err(InflateError::Header));
+    }
+
+    #[test]
+    fn adler32_of_known_input() {
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+    }
+}
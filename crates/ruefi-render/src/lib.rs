@@ -0,0 +1,25 @@
+//! Software renderer for Rüfi.
+//!
+//! Draws the game into a [`BackBuffer`](blitter::BackBuffer) in system memory, which is then
+//! copied to any linear 32 bpp framebuffer. It has no UEFI dependency, so scenes can be rendered
+//! headless and tested on the host.
+
+#![cfg_attr(not(test), no_std)]
+// Drawing calls take positions and colors as individual arguments.
+#![allow(clippy::too_many_arguments)]
+
+extern crate alloc;
+
+pub mod blitter;
+pub mod dirty;
+pub mod flush;
+mod font;
+mod inflate;
+pub mod png;
+pub mod postfx;
+pub mod video;
+
+/// The logo, converted from `assets/ruefi.png` to RGBA at build time.
+pub mod logo {
+    include!(concat!(env!("OUT_DIR"), "/assets_gen.rs"));
+}
@@ -0,0 +1,438 @@
+//! PNG decoder for images loaded at runtime, e.g. art replacing the compiled-in logo.
+//!
+//! Supports all standard color types and bit depths of non-interlaced images,
+//! including palette and `tRNS` transparency. Ancillary chunks are ignored.
+
+use crate::inflate::{self, InflateError};
+use alloc::vec::Vec;
+use core::fmt;
+use libm::floorf;
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+
+/// Largest accepted image, in pixels; bounds the memory needed for decoding.
+const MAX_PIXELS: usize = 4096 * 4096;
+
+/// A decoded image as 8-bit RGBA, row by row without padding.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub rgba: Vec<u8>,
+}
+
+impl Image {
+    /// The texel at (x, y); transparent outside the image.
+    #[inline]
+    pub fn texel(&self, x: isize, y: isize) -> [u8; 4] {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            return [0; 4];
+        }
+        let p = (y as usize * self.width + x as usize) * 4;
+        [
+            self.rgba[p],
+            self.rgba[p + 1],
+            self.rgba[p + 2],
+            self.rgba[p + 3],
+        ]
+    }
+
+    /// Bilinearly interpolate the texels around image-space position (u, v), where texel
+    /// centers lie at half-integer coordinates; fades to transparent past the edges.
+    pub fn sample_bilinear(&self, u: f32, v: f32) -> [u8; 4] {
+        let (u, v) = (u - 0.5, v - 0.5);
+        let (x0, y0) = (floorf(u), floorf(v));
+        let (fx, fy) = (u - x0, v - y0);
+        let (x0, y0) = (x0 as isize, y0 as isize);
+        let taps = [
+            (self.texel(x0, y0), (1.0 - fx) * (1.0 - fy)),
+            (self.texel(x0 + 1, y0), fx * (1.0 - fy)),
+            (self.texel(x0, y0 + 1), (1.0 - fx) * fy),
+            (self.texel(x0 + 1, y0 + 1), fx * fy),
+        ];
+        let mut out = [0u8; 4];
+        for (c, o) in out.iter_mut().enumerate() {
+            let sum: f32 = taps.iter().map(|(t, w)| t[c] as f32 * w).sum();
+            *o = (sum + 0.5) as u8;
+        }
+        out
+    }
+
+    /// Premultiply the color channels by alpha, as expected by
+    /// [`BackBuffer::blit_rgba`](crate::blitter::BackBuffer::blit_rgba).
+    pub fn premultiply(&mut self) {
+        for px in self.rgba.chunks_exact_mut(4) {
+            let a = px[3] as u16;
+            px[0] = ((px[0] as u16 * a) / 255) as u8;
+            px[1] = ((px[1] as u16 * a) / 255) as u8;
+            px[2] = ((px[2] as u16 * a) / 255) as u8;
+        }
+    }
+}
+
+/// Reasons a PNG file cannot be decoded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PngError {
+    Signature,             // not a PNG file
+    Truncated,             // file ends in the middle of a chunk or before `IEND`
+    Crc,                   // a chunk's CRC does not match
+    Header,                // missing or invalid `IHDR`
+    Unsupported,           // valid but unsupported, e.g. interlaced
+    TooLarge,              // image exceeds `MAX_PIXELS`
+    Palette,               // missing, invalid or too short palette
+    Data,                  // image data has the wrong size or an invalid filter type
+    Inflate(InflateError), // compressed image data is corrupt
+}
+
+impl fmt::Display for PngError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Signature => f.write_str("not a PNG file"),
+            Self::Truncated => f.write_str("file is truncated"),
+            Self::Crc => f.write_str("chunk CRC mismatch"),
+            Self::Header => f.write_str("invalid image header"),
+            Self::Unsupported => f.write_str("interlaced images are not supported"),
+            Self::TooLarge => f.write_str("image is too large"),
+            Self::Palette => f.write_str("invalid palette"),
+            Self::Data => f.write_str("invalid image data"),
+            Self::Inflate(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl From<InflateError> for PngError {
+    fn from(e: InflateError) -> Self {
+        Self::Inflate(e)
+    }
+}
+
+/// Color types of the `IHDR` chunk.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ColorType {
+    Gray,
+    Rgb,
+    Indexed,
+    GrayAlpha,
+    Rgba,
+}
+
+impl ColorType {
+    fn from_byte(b: u8) -> Option<Self> {
+        Some(match b {
+            0 => Self::Gray,
+            2 => Self::Rgb,
+            3 => Self::Indexed,
+            4 => Self::GrayAlpha,
+            6 => Self::Rgba,
+            _ => return None,
+        })
+    }
+
+    const fn channels(self) -> usize {
+        match self {
+            Self::Gray | Self::Indexed => 1,
+            Self::GrayAlpha => 2,
+            Self::Rgb => 3,
+            Self::Rgba => 4,
+        }
+    }
+
+    const fn allows_depth(self, depth: u8) -> bool {
+        match self {
+            Self::Gray => matches!(depth, 1 | 2 | 4 | 8 | 16),
+            Self::Indexed => matches!(depth, 1 | 2 | 4 | 8),
+            Self::Rgb | Self::GrayAlpha | Self::Rgba => matches!(depth, 8 | 16),
+        }
+    }
+}
+
+/// Decode a PNG file into straight (not premultiplied) RGBA.
+pub fn decode(data: &[u8]) -> Result<Image, PngError> {
+    let mut rest = data.strip_prefix(&SIGNATURE).ok_or(PngError::Signature)?;
+
+    let mut header = None;
+    let mut palette: Vec<[u8; 4]> = Vec::new();
+    let mut transparent: Option<[u16; 3]> = None; // `tRNS` color key of gray and RGB images
+    let mut idat = Vec::new();
+    loop {
+        let (Chunk { kind, body }, tail) = next_chunk(rest)?;
+        rest = tail;
+        match &kind {
+            b"IHDR" => header = Some(Header::parse(body)?),
+            b"PLTE" => {
+                if body.len() % 3 != 0 || body.len() > 256 * 3 {
+                    return Err(PngError::Palette);
+                }
+                palette = body
+                    .chunks_exact(3)
+                    .map(|c| [c[0], c[1], c[2], 255])
+                    .collect();
+            }
+            b"tRNS" => {
+                let h = header.as_ref().ok_or(PngError::Header)?;
+                match h.color {
+                    ColorType::Indexed => {
+                        if body.len() > palette.len() {
+                            return Err(PngError::Palette);
+                        }
+                        for (entry, &alpha) in palette.iter_mut().zip(body) {
+                            entry[3] = alpha;
+                        }
+                    }
+                    ColorType::Gray if body.len() == 2 => {
+                        let v = u16::from_be_bytes([body[0], body[1]]);
+                        transparent = Some([v; 3]);
+                    }
+                    ColorType::Rgb if body.len() == 6 => {
+                        let v = |i: usize| u16::from_be_bytes([body[i], body[i + 1]]);
+                        transparent = Some([v(0), v(2), v(4)]);
+                    }
+                    _ => {} // invalid for this color type; ignore like other ancillary chunks
+                }
+            }
+            b"IDAT" => idat.extend_from_slice(body),
+            b"IEND" => break,
+            _ => {}
+        }
+    }
+
+    let header = header.ok_or(PngError::Header)?;
+    if header.color == ColorType::Indexed && palette.is_empty() {
+        return Err(PngError::Palette);
+    }
+
+    let stride = header.stride();
+    let raw = inflate::zlib_decompress(&idat, header.height * (stride + 1))?;
+    if raw.len() != header.height * (stride + 1) {
+        return Err(PngError::Data);
+    }
+    let pixels = unfilter(&raw, stride, header.bytes_per_pixel())?;
+
+    let mut rgba = Vec::with_capacity(header.width * header.height * 4);
+    for row in pixels.chunks_exact(stride) {
+        header.expand_row(row, &palette, transparent, &mut rgba)?;
+    }
+    Ok(Image {
+        width: header.width,
+        height: header.height,
+        rgba,
+    })
+}
+
+/// A chunk's type and data.
+struct Chunk<'a> {
+    kind: [u8; 4],
+    body: &'a [u8],
+}
+
+/// Split off the next chunk after checking its CRC; also returns the remaining input.
+fn next_chunk(data: &[u8]) -> Result<(Chunk<'_>, &[u8]), PngError> {
+    let (len, rest) = data.split_first_chunk::<4>().ok_or(PngError::Truncated)?;
+    let len = u32::from_be_bytes(*len) as usize;
+    if rest.len() < len + 8 {
+        return Err(PngError::Truncated);
+    }
+    let (typed, rest) = rest.split_at(4 + len);
+    let (crc, rest) = rest.split_at(4);
+    if crc32(typed) != u32::from_be_bytes([crc[0], crc[1], crc[2], crc[3]]) {
+        return Err(PngError::Crc);
+    }
+    let kind = [typed[0], typed[1], typed[2], typed[3]];
+    let body = &typed[4..];
+    Ok((Chunk { kind, body }, rest))
+}
+
+/// The fields of `IHDR` relevant for decoding.
+struct Header {
+    width: usize,
+    height: usize,
+    depth: u8,
+    color: ColorType,
+}
+
+impl Header {
+    fn parse(body: &[u8]) -> Result<Self, PngError> {
+        let body: &[u8; 13] = body.try_into().map_err(|_| PngError::Header)?;
+        let width = u32::from_be_bytes([body[0], body[1], body[2], body[3]]) as usize;
+        let height = u32::from_be_bytes([body[4], body[5], body[6], body[7]]) as usize;
+        let depth = body[8];
+        let color = ColorType::from_byte(body[9]).ok_or(PngError::Header)?;
+        let (compression, filter, interlace) = (body[10], body[11], body[12]);
+
+        if width == 0
+            || height == 0
+            || !color.allows_depth(depth)
+            || compression != 0
+            || filter != 0
+        {
+            return Err(PngError::Header);
+        }
+        if interlace != 0 {
+            return Err(PngError::Unsupported);
+        }
+        if width.checked_mul(height).is_none_or(|n| n > MAX_PIXELS) {
+            return Err(PngError::TooLarge);
+        }
+        Ok(Self {
+            width,
+            height,
+            depth,
+            color,
+        })
+    }
+
+    /// Bytes per row of unfiltered image data.
+    fn stride(&self) -> usize {
+        (self.width * self.color.channels() * self.depth as usize).div_ceil(8)
+    }
+
+    /// Distance to the corresponding byte of the previous pixel, as used by the filters.
+    fn bytes_per_pixel(&self) -> usize {
+        (self.color.channels() * self.depth as usize).div_ceil(8)
+    }
+
+    /// Append one row of unfiltered image data as RGBA.
+    fn expand_row(
+        &self,
+        row: &[u8],
+        palette: &[[u8; 4]],
+        transparent: Option<[u16; 3]>,
+        out: &mut Vec<u8>,
+    ) -> Result<(), PngError> {
+        let channels = self.color.channels();
+        let depth = self.depth as usize;
+        // Full-precision sample `i` of the row
+        let sample = |i: usize| -> u16 {
+            match depth {
+                16 => u16::from_be_bytes([row[2 * i], row[2 * i + 1]]),
+                8 => row[i] as u16,
+                _ => {
+                    let bit = i * depth;
+                    let shift = 8 - depth - bit % 8;
+                    ((row[bit / 8] >> shift) & ((1 << depth) - 1)) as u16
+                }
+            }
+        };
+        // Scale a sample to 8 bits; 16-bit samples keep their high byte
+        let max = (1u32 << depth) - 1;
+        let to_u8 = |v: u16| match depth {
+            16 => (v >> 8) as u8,
+            _ => (v as u32 * 255 / max) as u8,
+        };
+
+        for x in 0..self.width {
+            let s = x * channels;
+            let px = match self.color {
+                ColorType::Indexed => *palette.get(sample(s) as usize).ok_or(PngError::Palette)?,
+                ColorType::Gray => {
+                    let v = sample(s);
+                    let a = if transparent == Some([v; 3]) { 0 } else { 255 };
+                    [to_u8(v), to_u8(v), to_u8(v), a]
+                }
+                ColorType::GrayAlpha => {
+                    let v = to_u8(sample(s));
+                    [v, v, v, to_u8(sample(s + 1))]
+                }
+                ColorType::Rgb => {
+                    let rgb = [sample(s), sample(s + 1), sample(s + 2)];
+                    let a = if transparent == Some(rgb) { 0 } else { 255 };
+                    [to_u8(rgb[0]), to_u8(rgb[1]), to_u8(rgb[2]), a]
+                }
+                ColorType::Rgba => [
+                    to_u8(sample(s)),
+                    to_u8(sample(s + 1)),
+                    to_u8(sample(s + 2)),
+                    to_u8(sample(s + 3)),
+                ],
+            };
+            out.extend_from_slice(&px);
+        }
+        Ok(())
+    }
+}
+
+/// Undo the per-row filters, returning the rows without their filter type bytes.
+fn unfilter(raw: &[u8], stride: usize, bpp: usize) -> Result<Vec<u8>, PngError> {
+    let rows = raw.len() / (stride + 1);
+    let mut out = alloc::vec![0u8; rows * stride];
+    for y in 0..rows {
+        let filter = raw[y * (stride + 1)];
+        let src = &raw[y * (stride + 1) + 1..(y + 1) * (stride + 1)];
+        let (done, cur) = out.split_at_mut(y * stride);
+        let cur = &mut cur[..stride];
+        let prev = if y > 0 {
+            &done[(y - 1) * stride..]
+        } else {
+            &[][..]
+        };
+        let up = |x: usize| prev.get(x).copied().unwrap_or(0);
+
+        for x in 0..stride {
+            let left = if x >= bpp { cur[x - bpp] } else { 0 };
+            let up_left = if x >= bpp { up(x - bpp) } else { 0 };
+            let predictor = match filter {
+                0 => 0,
+                1 => left,
+                2 => up(x),
+                3 => ((left as u16 + up(x) as u16) / 2) as u8,
+                4 => paeth(left, up(x), up_left),
+                _ => return Err(PngError::Data),
+            };
+            cur[x] = src[x].wrapping_add(predictor);
+        }
+    }
+    Ok(out)
+}
+
+/// The Paeth predictor: whichever neighbor is closest to `a + b - c`.
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let (pa, pb, pc) = (
+        (p - a as i16).abs(),
+        (p - b as i16).abs(),
+        (p - c as i16).abs(),
+    );
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+/// CRC-32 (ISO 3309) as used by PNG chunks.
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crc32_of_iend() {
+        assert_eq!(crc32(b"IEND"), 0xAE42_6082);
+    }
+
+    #[test]
+    fn paeth_picks_the_closest_neighbor() {
+        assert_eq!(paeth(10, 20, 10), 20);
+        assert_eq!(paeth(20, 10, 10), 20);
+        assert_eq!(paeth(10, 10, 20), 10);
+    }
+
+    #[test]
+    fn rejects_non_png_data() {
+        assert_eq!(decode(b"GIF89a"), Err(PngError::Signature));
+        assert_eq!(decode(&SIGNATURE), Err(PngError::Truncated));
+    }
+}
@@ -0,0 +1,245 @@
+//! Vector CRT post-processing applied to the finished frame before it is presented:
+//! phosphor persistence, bloom around bright lines and scanlines.
+
+use crate::blitter::BackBuffer;
+use alloc::vec::Vec;
+
+/// Fraction (out of 255) of the previous frame's brightness kept by the phosphor each frame.
+const PERSISTENCE_DECAY: u16 = 200;
+
+/// Luma above which pixels contribute to the bloom.
+const BLOOM_THRESHOLD: u16 = 128;
+
+/// Box blur radius of the bloom, in half-resolution pixels; blurred twice (separably).
+const BLOOM_RADIUS: usize = 3;
+
+/// Bloom intensity added to the frame, out of 256.
+const BLOOM_STRENGTH: u32 = 320;
+
+/// Brightness (out of 255) of every other row when scanlines are enabled.
+const SCANLINE_LEVEL: u16 = 150;
+
+/// Post-processing state; each effect can be toggled at any time.
+#[derive(Default)]
+pub struct PostFx {
+    pub persistence: bool,
+    pub bloom: bool,
+    pub scanlines: bool,
+    width: usize,
+    height: usize,
+    frame: Vec<u8>, // RGB of the frame being processed
+    trail: Vec<u8>, // RGB of the previous frame, for persistence
+    glow: Vec<u16>, // half-resolution RGB bright pass for the bloom
+    tmp: Vec<u16>,  // scratch row/column sums of the blur
+}
+
+impl PostFx {
+    pub fn new(persistence: bool, bloom: bool, scanlines: bool) -> Self {
+        Self {
+            persistence,
+            bloom,
+            scanlines,
+            ..Self::default()
+        }
+    }
+
+    /// Whether any effect is enabled.
+    #[inline]
+    pub fn is_active(&self) -> bool {
+        self.persistence || self.bloom || self.scanlines
+    }
+
+    /// Process the finished frame in place.
+    pub fn apply(&mut self, back: &mut BackBuffer) {
+        if !self.is_active() {
+            // Drop the trail so re-enabling persistence does not show a stale frame
+            self.trail.clear();
+            return;
+        }
+        let (w, h) = (back.width, back.height);
+        if (w, h) != (self.width, self.height) {
+            self.width = w;
+            self.height = h;
+            self.frame = alloc::vec![0; w * h * 3];
+            self.trail.clear();
+        }
+        back.read_rgb_all(&mut self.frame);
+
+        if self.persistence {
+            self.apply_persistence();
+        } else {
+            self.trail.clear();
+        }
+        if self.bloom {
+            self.apply_bloom();
+        }
+        if self.scanlines {
+            self.apply_scanlines();
+        }
+        back.write_rgb_all(&self.frame);
+    }
+
+    /// Keep the brighter of the new frame and the decayed previous one.
+    fn apply_persistence(&mut self) {
+        if self.trail.len() != self.frame.len() {
+            self.trail = self.frame.clone();
+            return;
+        }
+        for (c, t) in self.frame.iter_mut().zip(self.trail.iter_mut()) {
+            let decayed = ((*t as u16 * PERSISTENCE_DECAY) / 255) as u8;
+            *c = (*c).max(decayed);
+            *t = *c;
+        }
+    }
+
+    /// Extract bright pixels at half resolution, blur them separably and add them back.
+    fn apply_bloom(&mut self) {
+        let (w, h) = (self.width, self.height);
+        let (hw, hh) = (w.div_ceil(2), h.div_ceil(2));
+        self.glow.clear();
+        self.glow.resize(hw * hh * 3, 0);
+
+        // Bright pass: average 2x2 blocks and keep those above the luma threshold
+        for y in 0..h {
+            for x in 0..w {
+                let p = (y * w + x) * 3;
+                let g = ((y / 2) * hw + x / 2) * 3;
+                for c in 0..3 {
+                    self.glow[g + c] += self.frame[p + c] as u16;
+                }
+            }
+        }
+        for px in self.glow.chunks_exact_mut(3) {
+            let [r, g, b] = [px[0] / 4, px[1] / 4, px[2] / 4];
+            let luma = (r * 54 + g * 183 + b * 19) >> 8;
+            let keep = luma >= BLOOM_THRESHOLD;
+            px.copy_from_slice(&if keep { [r, g, b] } else { [0, 0, 0] });
+        }
+
+        blur(&mut self.glow, &mut self.tmp, hw, hh, 3, 3 * hw);
+        blur(&mut self.glow, &mut self.tmp, hh, hw, 3 * hw, 3);
+
+        for y in 0..h {
+            for x in 0..w {
+                let p = (y * w + x) * 3;
+                let g = ((y / 2) * hw + x / 2) * 3;
+                for c in 0..3 {
+                    let add = (self.glow[g + c] as u32 * BLOOM_STRENGTH) >> 8;
+                    self.frame[p + c] = (self.frame[p + c] as u32 + add).min(255) as u8;
+                }
+            }
+        }
+    }
+
+    /// Darken every other row.
+    fn apply_scanlines(&mut self) {
+        let row_len = self.width * 3;
+        for row in self.frame.chunks_exact_mut(row_len).skip(1).step_by(2) {
+            for c in row {
+                *c = ((*c as u16 * SCANLINE_LEVEL) / 255) as u8;
+            }
+        }
+    }
+}
+
+/// Box blur along one axis of an RGB `u16` image: `lines` lines of `len` pixels each, where
+/// consecutive pixels of a line are `step` elements apart and lines start `line_step` apart
+/// (rows: `step` 3, `line_step` 3 * width; columns: the other way around).
+fn blur(
+    data: &mut [u16],
+    tmp: &mut Vec<u16>,
+    len: usize,
+    lines: usize,
+    step: usize,
+    line_step: usize,
+) {
+    let taps = (2 * BLOOM_RADIUS + 1) as u32;
+    tmp.clear();
+    tmp.resize(len * 3, 0);
+    for line in 0..lines {
+        let base = line * line_step;
+        for c in 0..3 {
+            let at = |i: usize| base + i * step + c;
+            // Running sum over the window [i - r, i + r], zero outside the line
+            let mut sum: u32 = (0..=BLOOM_RADIUS.min(len - 1))
+                .map(|i| data[at(i)] as u32)
+                .sum();
+            for i in 0..len {
+                tmp[i * 3 + c] = (sum / taps) as u16;
+                if i + BLOOM_RADIUS + 1 < len {
+                    sum += data[at(i + BLOOM_RADIUS + 1)] as u32;
+                }
+                if i >= BLOOM_RADIUS {
+                    sum -= data[at(i - BLOOM_RADIUS)] as u32;
+                }
+            }
+        }
+        for i in 0..len {
+            for c in 0..3 {
+                data[base + i * step + c] = tmp[i * 3 + c];
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blitter::PixelFormat;
+
+    fn frame_with_dot(x: isize, y: isize, v: u8) -> BackBuffer {
+        let mut back = BackBuffer::new(32, 32, PixelFormat::Rgb);
+        back.put_pixel(x, y, v, v, v);
+        back
+    }
+
+    #[test]
+    fn disabled_leaves_frame_untouched() {
+        let mut back = frame_with_dot(5, 5, 255);
+        let before = back.pixels().to_vec();
+        PostFx::default().apply(&mut back);
+        assert_eq!(back.pixels(), &before[..]);
+    }
+
+    #[test]
+    fn persistence_decays_previous_frame() {
+        let mut fx = PostFx::new(true, false, false);
+        let mut back = frame_with_dot(5, 5, 255);
+        fx.apply(&mut back);
+        back.clear_rgb(0, 0, 0);
+        fx.apply(&mut back);
+        let (r, _, _) = back.get_pixel(5, 5);
+        assert_eq!(r, 200);
+        back.clear_rgb(0, 0, 0);
+        fx.apply(&mut back);
+        assert!(back.get_pixel(5, 5).0 < 200);
+    }
+
+    #[test]
+    fn scanlines_darken_odd_rows_only() {
+        let mut fx = PostFx::new(false, false, true);
+        let mut back = BackBuffer::new(4, 4, PixelFormat::Rgb);
+        back.clear_rgb(255, 255, 255);
+        fx.apply(&mut back);
+        assert_eq!(back.get_pixel(0, 0), (255, 255, 255));
+        assert_eq!(back.get_pixel(0, 1), (150, 150, 150));
+        assert_eq!(back.get_pixel(0, 2), (255, 255, 255));
+    }
+
+    #[test]
+    fn bloom_spreads_bright_pixels_only() {
+        let mut fx = PostFx::new(false, true, false);
+        let mut back = BackBuffer::new(32, 32, PixelFormat::Rgb);
+        for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+            back.put_pixel(10 + dx, 10 + dy, 255, 255, 255);
+            back.put_pixel(20 + dx, 20 + dy, 80, 80, 80);
+        }
+        fx.apply(&mut back);
+        assert!(back.get_pixel(14, 10).0 > 0, "glow around bright block");
+        assert_eq!(
+            back.get_pixel(24, 20),
+            (0, 0, 0),
+            "no glow around dim block"
+        );
+    }
+}
@@ -0,0 +1,88 @@
+use crate::blitter::PixelFormat;
+
+/// A display mode offered by the firmware.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VideoMode {
+    pub index: usize, // position in the list of modes queried from the firmware
+    pub width: usize,
+    pub height: usize,
+    pub format: Option<PixelFormat>, // `None` if there is no linear framebuffer (blt only)
+}
+
+impl VideoMode {
+    /// Whether the mode has a directly writable framebuffer with 32 bits per pixel.
+    #[inline]
+    pub fn is_linear_32bpp(&self) -> bool {
+        self.format.is_some_and(|f| f.bytes_per_pixel() == 4)
+    }
+}
+
+/// Pick the mode closest to the preferred resolution. Modes with a 32 bpp linear
+/// framebuffer always win over others; ties are broken in favor of the larger mode.
+pub fn preferred_mode(modes: &[VideoMode], width: usize, height: usize) -> Option<&VideoMode> {
+    modes.iter().min_by_key(|m| {
+        let distance = m.width.abs_diff(width) + m.height.abs_diff(height);
+        (
+            !m.is_linear_32bpp(),
+            distance,
+            usize::MAX - m.width * m.height,
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mode(index: usize, width: usize, height: usize, format: Option<PixelFormat>) -> VideoMode {
+        VideoMode {
+            index,
+            width,
+            height,
+            format,
+        }
+    }
+
+    #[test]
+    fn picks_closest_resolution() {
+        let modes = [
+            mode(0, 800, 600, Some(PixelFormat::Bgr)),
+            mode(1, 1280, 800, Some(PixelFormat::Bgr)),
+            mode(2, 1920, 1080, Some(PixelFormat::Bgr)),
+        ];
+        assert_eq!(preferred_mode(&modes, 1280, 720).unwrap().index, 1);
+        assert_eq!(preferred_mode(&modes, 1920, 1200).unwrap().index, 2);
+    }
+
+    #[test]
+    fn prefers_linear_32bpp_framebuffers() {
+        let rgb565 = PixelFormat::Bitmask {
+            red: 0xF800,
+            green: 0x07E0,
+            blue: 0x001F,
+            reserved: 0,
+        };
+        let modes = [
+            mode(0, 1280, 720, None),
+            mode(1, 1280, 720, Some(rgb565)),
+            mode(2, 1024, 768, Some(PixelFormat::Rgb)),
+        ];
+        assert_eq!(preferred_mode(&modes, 1280, 720).unwrap().index, 2);
+    }
+
+    #[test]
+    fn falls_back_to_any_mode() {
+        let modes = [mode(3, 640, 480, None)];
+        assert_eq!(preferred_mode(&modes, 1280, 720).unwrap().index, 3);
+        assert!(preferred_mode(&[], 1280, 720).is_none());
+    }
+
+    #[test]
+    fn ties_prefer_the_larger_mode() {
+        let modes = [
+            mode(0, 1180, 720, Some(PixelFormat::Bgr)),
+            mode(1, 1380, 720, Some(PixelFormat::Bgr)),
+        ];
+        assert_eq!(preferred_mode(&modes, 1280, 720).unwrap().index, 1);
+    }
+}
@@ -0,0 +1,377 @@
+//! Golden-image tests: render known scenes headless and compare them against
+//! the PNG reference images in `tests/golden`.
+//!
+//! Set `UPDATE_GOLDEN=1` to (re)generate the reference images after an intended change.
+//! On mismatch, the actual rendering is written to the test's temporary directory.
+
+use ruefi_core::asteroid::Asteroid;
+use ruefi_core::projectile::Projectile;
+use ruefi_core::ship::Ship;
+use ruefi_render::blitter::{BackBuffer, FillRule, LineStyle, PixelFormat, Sampling, TextAlign};
+use ruefi_render::logo;
+use ruefi_render::png::Image;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+const W: usize = 160;
+const H: usize = 120;
+
+fn golden_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden")
+}
+
+/// Convert the back buffer to tightly packed RGB8, independent of its pixel format.
+fn to_rgb(back: &BackBuffer) -> Vec<u8> {
+    let mut out = Vec::with_capacity(back.width * back.height * 3);
+    for y in 0..back.height {
+        for x in 0..back.width {
+            let (r, g, b) = back.get_pixel(x, y);
+            out.extend_from_slice(&[r, g, b]);
+        }
+    }
+    out
+}
+
+fn write_png(path: &Path, width: usize, height: usize, rgb: &[u8]) {
+    let file = BufWriter::new(File::create(path).unwrap());
+    let mut enc = png::Encoder::new(file, width as u32, height as u32);
+    enc.set_color(png::ColorType::Rgb);
+    enc.set_depth(png::BitDepth::Eight);
+    enc.write_header().unwrap().write_image_data(rgb).unwrap();
+}
+
+/// Decode a PNG into RGBA8, returning (width, height, pixels).
+fn read_png_rgba(path: &Path) -> (usize, usize, Vec<u8>) {
+    let mut dec = png::Decoder::new(std::io::BufReader::new(File::open(path).unwrap()));
+    dec.set_transformations(png::Transformations::EXPAND | png::Transformations::ALPHA);
+    let mut reader = dec.read_info().unwrap();
+    let mut buf = vec![0; reader.output_buffer_size().unwrap()];
+    let info = reader.next_frame(&mut buf).unwrap();
+    buf.truncate(info.buffer_size());
+    let (w, h) = (info.width as usize, info.height as usize);
+    let rgba = match info.color_type {
+        png::ColorType::Rgba => buf,
+        png::ColorType::GrayscaleAlpha => buf
+            .chunks(2)
+            .flat_map(|p| [p[0], p[0], p[0], p[1]])
+            .collect(),
+        other => panic!("unexpected color type {other:?}"),
+    };
+    (w, h, rgba)
+}
+
+/// Compare the rendering with the named golden image.
+fn assert_golden(name: &str, back: &BackBuffer) {
+    let rgb = to_rgb(back);
+    let path = golden_dir().join(format!("{name}.png"));
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        write_png(&path, back.width, back.height, &rgb);
+        return;
+    }
+
+    let (w, h, expected) = read_png_rgba(&path);
+    let expected: Vec<u8> = expected
+        .chunks(4)
+        .flat_map(|p| [p[0], p[1], p[2]])
+        .collect();
+    if (w, h) != (back.width, back.height) || expected != rgb {
+        let actual = Path::new(env!("CARGO_TARGET_TMPDIR")).join(format!("{name}.actual.png"));
+        write_png(&actual, back.width, back.height, &rgb);
+        panic!(
+            "rendering of `{name}` differs from {}; actual output written to {}",
+            path.display(),
+            actual.display()
+        );
+    }
+}
+
+/// Render a scene into several pixel formats and check that they agree before returning one.
+fn render(scene: impl Fn(&mut BackBuffer)) -> BackBuffer {
+    let mut rgb = BackBuffer::new(W, H, PixelFormat::Rgb);
+    scene(&mut rgb);
+    let expected = to_rgb(&rgb);
+    let others = [
+        PixelFormat::Bgr,
+        // 8 bits per channel, but in an unusual position
+        PixelFormat::Bitmask {
+            red: 0x0000_FF00,
+            green: 0x00FF_0000,
+            blue: 0xFF00_0000,
+            reserved: 0x0000_00FF,
+        },
+    ];
+    for format in others {
+        let mut back = BackBuffer::new(W, H, format);
+        scene(&mut back);
+        assert!(to_rgb(&back) == expected, "{format:?} disagrees with Rgb");
+    }
+    rgb
+}
+
+fn rock(x: f32, y: f32, radius: f32, base_angle: f32) -> Asteroid {
+    Asteroid {
+        x,
+        y,
+        vx: 0.0,
+        vy: 0.0,
+        radius,
+        base_angle,
+        jitter: [1.0, 0.8, 1.2, 0.9, 1.1, 0.85],
+        split_stage: 2,
+    }
+}
+
+#[test]
+fn ship() {
+    let back = render(|back| {
+        let mut ship = Ship::new(W, H);
+        ship.angle = 0.6;
+        back.draw_ship(&ship, LineStyle::HAIRLINE, 92, 127, 255);
+    });
+    assert_golden("ship", &back);
+}
+
+#[test]
+fn wrapped_asteroids() {
+    let back = render(|back| {
+        // One in the corner, wrapping across all four edges, and one in the field
+        back.draw_asteroid_wrapped(
+            &rock(4.0, 6.0, 20.0, 0.3),
+            W,
+            H,
+            LineStyle::HAIRLINE,
+            200,
+            200,
+            200,
+        );
+        back.draw_asteroid_wrapped(
+            &rock(90.0, 60.0, 14.0, 1.1),
+            W,
+            H,
+            LineStyle::HAIRLINE,
+            200,
+            200,
+            200,
+        );
+    });
+    assert_golden("wrapped_asteroids", &back);
+}
+
+#[test]
+fn projectiles() {
+    let back = render(|back| {
+        let mut ship = Ship::new(W, H);
+        for i in 0..8 {
+            ship.angle = i as f32 * core::f32::consts::FRAC_PI_4;
+            let mut p = Projectile::spawn_from_ship(&ship, 720.0);
+            p.update(1.0 / 30.0);
+            back.draw_projectile(p.x, p.y, p.vx, p.vy, 5.0, LineStyle::HAIRLINE, 255, 255, 0);
+        }
+    });
+    assert_golden("projectiles", &back);
+}
+
+#[test]
+fn text() {
+    let back = render(|back| {
+        back.draw_text("Left", 4, 4, 1, TextAlign::Left, 255, 255, 255);
+        back.draw_text("CENTER\nx2", 80, 30, 2, TextAlign::Center, 0, 255, 0);
+        back.draw_text("right", 156, 100, 1, TextAlign::Right, 255, 0, 0);
+    });
+    assert_golden("text", &back);
+}
+
+#[test]
+fn hud() {
+    let back = render(|back| back.draw_hud(12340, 3, 7));
+    assert_golden("hud", &back);
+}
+
+#[test]
+fn alpha_blend() {
+    // A premultiplied sprite: red fading out from the center, with a soft white ring
+    let size = 48;
+    let mut sprite = vec![0u8; size * size * 4];
+    for y in 0..size {
+        for x in 0..size {
+            let (dx, dy) = (x as f32 - 23.5, y as f32 - 23.5);
+            let d = (dx * dx + dy * dy).sqrt() / 24.0;
+            let a = ((1.0 - d).max(0.0) * 255.0) as u8;
+            let ring = if (0.6..0.7).contains(&d) { a / 2 } else { 0 };
+            sprite[(y * size + x) * 4..][..4].copy_from_slice(&[a, ring, ring, a]);
+        }
+    }
+    let back = render(|back| {
+        // Striped background so the blending is visible against several colors
+        for y in 0..H as isize {
+            let (r, g, b) = if (y / 10) % 2 == 0 {
+                (0, 0, 160)
+            } else {
+                (40, 200, 40)
+            };
+            back.draw_line(0, y, W as isize - 1, y, r, g, b);
+        }
+        back.blit_rgba(&sprite, size, size, 10, 20, 255);
+        back.blit_rgba(&sprite, size, size, 60, 20, 128);
+        back.blit_rgba(&sprite, size, size, 130, 60, 255); // clipped on the right and bottom
+    });
+    assert_golden("alpha_blend", &back);
+}
+
+#[test]
+fn polygons() {
+    let back = render(|back| {
+        // Concave arrow head, a self-intersecting star under both fill rules, and a panel
+        let chevron = [(10.0, 10.0), (40.0, 25.0), (10.0, 40.0), (20.0, 25.0)];
+        back.fill_polygon(&chevron, FillRule::EvenOdd, 0, 200, 255);
+        let star: Vec<(f32, f32)> = (0..5)
+            .map(|i| {
+                let t = i as f32 * 4.0 * std::f32::consts::PI / 5.0;
+                (18.0 * t.sin(), -18.0 * t.cos())
+            })
+            .collect();
+        for (cx, rule) in [(75.0, FillRule::EvenOdd), (120.0, FillRule::NonZero)] {
+            let moved: Vec<_> = star.iter().map(|&(x, y)| (x + cx, y + 28.0)).collect();
+            back.fill_polygon(&moved, rule, 255, 128, 0);
+        }
+        back.draw_panel(-5, 60, 50, 40);
+
+        // Solid objects with outlines, including a filled asteroid wrapping across an edge
+        let ship = Ship::new(90, 80);
+        back.fill_ship(&ship, 30, 45, 110);
+        back.draw_ship(&ship, LineStyle::HAIRLINE, 92, 127, 255);
+        let a = rock(150.0, 95.0, 18.0, 0.4);
+        back.fill_asteroid_wrapped(&a, W, H, 70, 70, 70);
+        back.draw_asteroid_wrapped(&a, W, H, LineStyle::HAIRLINE, 200, 200, 200);
+    });
+    assert_golden("polygons", &back);
+}
+
+#[test]
+fn lines() {
+    let back = render(|back| {
+        // A fan of anti-aliased lines next to hairlines, then thick lines with and without AA
+        for i in 0..8 {
+            let t = i as f32 * std::f32::consts::PI / 16.0;
+            let (dx, dy) = (t.cos() * 36.0, t.sin() * 36.0);
+            back.draw_line_styled(
+                4.0,
+                4.0,
+                4.0 + dx,
+                4.0 + dy,
+                LineStyle::smooth(1.0),
+                255,
+                255,
+                255,
+            );
+            back.draw_line_styled(
+                44.0,
+                4.0,
+                44.0 + dx,
+                4.0 + dy,
+                LineStyle::HAIRLINE,
+                255,
+                255,
+                255,
+            );
+        }
+        let thick = LineStyle {
+            width: 4.0,
+            anti_aliased: false,
+        };
+        back.draw_line_styled(90.0, 8.0, 150.0, 30.0, thick, 255, 128, 0);
+        back.draw_line_styled(90.0, 20.0, 150.0, 42.0, LineStyle::smooth(4.0), 255, 128, 0);
+        back.draw_line_styled(-10.0, 110.0, 60.0, 130.0, LineStyle::smooth(3.0), 0, 255, 0);
+
+        let ship = Ship::new(60, 150);
+        back.draw_ship(&ship, LineStyle::smooth(1.5), 92, 127, 255);
+        let a = rock(110.0, 85.0, 22.0, 0.7);
+        back.draw_asteroid_wrapped(&a, W, H, LineStyle::smooth(2.0), 200, 200, 200);
+    });
+    assert_golden("lines", &back);
+}
+
+/// A 16x24 arrow pointing up: opaque yellow body with a half-transparent red outline.
+fn arrow_sprite() -> Image {
+    let (width, height) = (16, 24);
+    let mut rgba = vec![0u8; width * height * 4];
+    for y in 0..height {
+        // Widens from the tip at the top to the full width at the bottom
+        let half = (y + 1) as f32 * width as f32 / (2 * height) as f32;
+        for x in 0..width {
+            let d = (x as f32 + 0.5 - width as f32 / 2.0).abs();
+            let px = if d < half - 1.5 {
+                [255, 220, 0, 255]
+            } else if d < half {
+                [128, 0, 0, 128]
+            } else {
+                continue;
+            };
+            rgba[(y * width + x) * 4..][..4].copy_from_slice(&px);
+        }
+    }
+    Image {
+        width,
+        height,
+        rgba,
+    }
+}
+
+#[test]
+fn sprites() {
+    let arrow = arrow_sprite();
+    let back = render(|back| {
+        back.clear_rgb(30, 30, 60);
+        back.draw_sprite(&arrow, 20.0, 30.0, 0.0, 1.0, Sampling::Nearest, 255);
+        back.draw_sprite(&arrow, 55.0, 30.0, 0.5, 1.5, Sampling::Nearest, 255);
+        back.draw_sprite(&arrow, 100.0, 30.0, 0.5, 1.5, Sampling::Bilinear, 255);
+        back.draw_sprite(&arrow, 140.0, 30.0, 3.0, 0.75, Sampling::Bilinear, 160);
+        // Clipped at the left and top edges, and wrapped across the bottom-right corner
+        back.draw_sprite(&arrow, 2.0, 70.0, -1.2, 2.0, Sampling::Bilinear, 255);
+        back.blit_rgba(&arrow.rgba, 16, 24, 60, -10, 255);
+        back.draw_sprite_wrapped(&arrow, 158.0, 118.0, 2.4, 1.0, Sampling::Nearest, 255, W, H);
+    });
+    assert_golden("sprites", &back);
+}
+
+/// The logo's reference image is the checked-in source asset itself:
+/// blitting it over black must reproduce its premultiplied colors exactly.
+#[test]
+fn logo() {
+    let asset = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../assets/ruefi.png");
+    let (w, h, rgba) = read_png_rgba(&asset);
+    assert_eq!((w, h), (logo::LOGO_WIDTH, logo::LOGO_HEIGHT));
+
+    let (dx, dy) = (3, 5);
+    let mut back = BackBuffer::new(w + 2 * dx, h + 2 * dy, PixelFormat::Bgr);
+    back.blit_rgba(logo::LOGO_RGBA, w, h, dx as isize, dy as isize, 255);
+
+    for y in 0..back.height {
+        for x in 0..back.width {
+            let inside = (dx..dx + w).contains(&x) && (dy..dy + h).contains(&y);
+            let expected = if inside {
+                let p = &rgba[((y - dy) * w + (x - dx)) * 4..][..4];
+                let a = p[3] as u16;
+                let premul = |c: u8| ((c as u16 * a) / 255) as u8;
+                (premul(p[0]), premul(p[1]), premul(p[2]))
+            } else {
+                (0, 0, 0)
+            };
+            assert_eq!(back.get_pixel(x, y), expected, "pixel ({x}, {y})");
+        }
+    }
+}
+
+#[test]
+fn flush_to_memory_surface_honors_pitch() {
+    let mut back = BackBuffer::new(4, 3, PixelFormat::Bgr);
+    back.put_pixel(1, 2, 10, 20, 30);
+    let pitch = 4 * 4 + 8;
+    let mut surface = vec![0xAA; pitch * 3];
+    back.flush_to(&mut surface, pitch);
+    let p = 2 * pitch + 4;
+    assert_eq!(&surface[p..p + 3], &[30, 20, 10]);
+    // Padding between rows is left untouched
+    assert!(surface[16..24].iter().all(|&b| b == 0xAA));
+}
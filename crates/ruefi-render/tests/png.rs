@@ -0,0 +1,158 @@
+//! Cross-checks the runtime PNG decoder against the `png` crate, using the checked-in
+//! assets and golden images as well as synthetic images in every color type and bit depth.
+
+use png::{BitDepth, ColorType, Filter, Transformations};
+use ruefi_render::png::{PngError, decode};
+use std::path::Path;
+
+/// Decode with the `png` crate, expanded to 8-bit RGBA.
+fn reference_rgba(data: &[u8]) -> (usize, usize, Vec<u8>) {
+    let mut decoder = png::Decoder::new(std::io::Cursor::new(data));
+    decoder.set_transformations(
+        Transformations::EXPAND | Transformations::STRIP_16 | Transformations::ALPHA,
+    );
+    let mut reader = decoder.read_info().unwrap();
+    let mut buf = vec![0; reader.output_buffer_size().unwrap()];
+    let info = reader.next_frame(&mut buf).unwrap();
+    buf.truncate(info.buffer_size());
+    let rgba = match info.color_type {
+        ColorType::Rgba => buf,
+        ColorType::GrayscaleAlpha => buf
+            .chunks_exact(2)
+            .flat_map(|p| [p[0], p[0], p[0], p[1]])
+            .collect(),
+        other => panic!("unexpected output color type {other:?}"),
+    };
+    (info.width as usize, info.height as usize, rgba)
+}
+
+fn assert_matches_reference(data: &[u8], what: &str) {
+    let image = decode(data).unwrap_or_else(|e| panic!("{what}: {e}"));
+    let (w, h, rgba) = reference_rgba(data);
+    assert_eq!((image.width, image.height), (w, h), "{what}: size");
+    assert!(image.rgba == rgba, "{what}: pixels differ");
+}
+
+/// Encode a synthetic image whose samples cover the full range of the bit depth.
+fn encode(
+    color: ColorType,
+    depth: BitDepth,
+    filter: Filter,
+    palette: Option<(&[u8], &[u8])>,
+    trns: Option<&[u8]>,
+) -> Vec<u8> {
+    let (w, h) = (13u32, 7u32);
+    let bits = depth as usize * color.samples();
+    let stride = (w as usize * bits).div_ceil(8);
+    let data: Vec<u8> = (0..stride * h as usize)
+        .map(|i| (i * 37 + i / stride * 11) as u8)
+        .collect();
+
+    let mut out = Vec::new();
+    let mut encoder = png::Encoder::new(&mut out, w, h);
+    encoder.set_color(color);
+    encoder.set_depth(depth);
+    encoder.set_filter(filter);
+    if let Some((plte, alpha)) = palette {
+        encoder.set_palette(plte.to_vec());
+        encoder.set_trns(alpha.to_vec());
+    }
+    if let Some(trns) = trns {
+        encoder.set_trns(trns.to_vec());
+    }
+    let mut writer = encoder.write_header().unwrap();
+    writer.write_image_data(&data).unwrap();
+    writer.finish().unwrap();
+    out
+}
+
+#[test]
+fn decodes_checked_in_images() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let mut paths = vec![root.join("../../assets/font.png")];
+    for entry in std::fs::read_dir(root.join("tests/golden")).unwrap() {
+        paths.push(entry.unwrap().path());
+    }
+    for path in paths {
+        let data = std::fs::read(&path).unwrap();
+        assert_matches_reference(&data, &path.display().to_string());
+    }
+}
+
+#[test]
+fn decodes_all_color_types_and_depths() {
+    let cases = [
+        (ColorType::Grayscale, BitDepth::One),
+        (ColorType::Grayscale, BitDepth::Two),
+        (ColorType::Grayscale, BitDepth::Four),
+        (ColorType::Grayscale, BitDepth::Eight),
+        (ColorType::Grayscale, BitDepth::Sixteen),
+        (ColorType::GrayscaleAlpha, BitDepth::Eight),
+        (ColorType::GrayscaleAlpha, BitDepth::Sixteen),
+        (ColorType::Rgb, BitDepth::Eight),
+        (ColorType::Rgb, BitDepth::Sixteen),
+        (ColorType::Rgba, BitDepth::Eight),
+        (ColorType::Rgba, BitDepth::Sixteen),
+    ];
+    let filters = [
+        Filter::NoFilter,
+        Filter::Sub,
+        Filter::Up,
+        Filter::Avg,
+        Filter::Paeth,
+    ];
+    for (color, depth) in cases {
+        for filter in filters {
+            let data = encode(color, depth, filter, None, None);
+            assert_matches_reference(&data, &format!("{color:?} {depth:?} {filter:?}"));
+        }
+    }
+}
+
+#[test]
+fn decodes_palettes_and_transparency() {
+    let plte: Vec<u8> = (0..=255u8).flat_map(|i| [i, 255 - i, i / 2]).collect();
+    let alpha: Vec<u8> = (0..=255u8).map(|i| i.wrapping_mul(3)).collect();
+    for depth in [
+        BitDepth::One,
+        BitDepth::Two,
+        BitDepth::Four,
+        BitDepth::Eight,
+    ] {
+        let data = encode(
+            ColorType::Indexed,
+            depth,
+            Filter::Paeth,
+            Some((&plte, &alpha)),
+            None,
+        );
+        assert_matches_reference(&data, &format!("indexed {depth:?}"));
+    }
+
+    // Color keys matching one of the synthetic samples
+    let gray = encode(
+        ColorType::Grayscale,
+        BitDepth::Eight,
+        Filter::Sub,
+        None,
+        Some(&[0, 37]),
+    );
+    assert_matches_reference(&gray, "gray color key");
+    let rgb = encode(
+        ColorType::Rgb,
+        BitDepth::Eight,
+        Filter::Up,
+        None,
+        Some(&[0, 0, 0, 37, 0, 74]),
+    );
+    assert_matches_reference(&rgb, "rgb color key");
+}
+
+#[test]
+fn rejects_corrupted_files() {
+    let mut data = encode(ColorType::Rgb, BitDepth::Eight, Filter::Paeth, None, None);
+    assert!(decode(&data[..data.len() - 20]).is_err());
+    let last = data.len() - 1;
+    data[last] ^= 0xFF; // IEND CRC
+    assert_eq!(decode(&data), Err(PngError::Crc));
+}
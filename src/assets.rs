@@ -0,0 +1,66 @@
+use crate::esp;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use ruefi_render::logo;
+use ruefi_render::png::{self, Image};
+
+/// Folder next to the executable holding replacement art.
+const ASSETS_DIR: &str = "assets";
+
+/// Images used by the game: loaded from the `assets` folder if present,
+/// otherwise the versions compiled into the binary. All images are premultiplied RGBA.
+pub struct Assets {
+    pub logo: Image,
+    pub ship: Option<Image>, // drawn nose up; the vector ship is used if absent
+    pub asteroid: Option<Image>, // the vector outline is used if absent
+}
+
+impl Assets {
+    /// Load all assets; files that are present but cannot be decoded are reported
+    /// in the returned messages and replaced by the compiled-in versions.
+    pub fn load() -> (Self, Vec<String>) {
+        let dir = format!("{}\\{ASSETS_DIR}", esp::image_dir().unwrap_or_default());
+        let mut errors = Vec::new();
+
+        let logo = load_png(&dir, "logo.png", &mut errors).unwrap_or_else(|| Image {
+            width: logo::LOGO_WIDTH,
+            height: logo::LOGO_HEIGHT,
+            rgba: logo::LOGO_RGBA.to_vec(),
+        });
+
+        let ship = load_png(&dir, "ship.png", &mut errors);
+        let asteroid = load_png(&dir, "asteroid.png", &mut errors);
+
+        (
+            Self {
+                logo,
+                ship,
+                asteroid,
+            },
+            errors,
+        )
+    }
+}
+
+/// Load and premultiply a PNG image; `None` if it is missing or invalid.
+fn load_png(dir: &str, name: &str, errors: &mut Vec<String>) -> Option<Image> {
+    let path = format!("{dir}\\{name}");
+    let data = match esp::read_file(&path) {
+        Ok(data) => data?,
+        Err(message) => {
+            errors.push(message);
+            return None;
+        }
+    };
+    match png::decode(&data) {
+        Ok(mut image) => {
+            image.premultiply();
+            Some(image)
+        }
+        Err(e) => {
+            errors.push(format!("{path}: {e}"));
+            None
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use core::arch::x86_64::_rdtsc;
+use uefi::boot::{self, EventType, TimerTrigger, Tpl};
+use uefi::{Event, Status};
+
+/// Microseconds spent busy-waiting to calibrate the TSC against `boot::stall`.
+const CALIBRATION_US: usize = 10_000;
+
+/// Frame clock: a periodic UEFI timer event paces the main loop, while the
+/// time stamp counter (calibrated once at startup) measures the elapsed time.
+pub struct FrameClock {
+    event: Event,
+    tsc_per_sec: f32,
+    last_tsc: u64,
+}
+
+impl FrameClock {
+    /// Create the clock with a timer period given in units of 100 ns.
+    pub fn new(period_100ns: u64) -> uefi::Result<Self> {
+        // SAFETY: plain timer event without notification function or context.
+        let event = unsafe { boot::create_event(EventType::TIMER, Tpl::APPLICATION, None, None) }?;
+        if let Err(e) = boot::set_timer(&event, TimerTrigger::Periodic(period_100ns)) {
+            let _ = boot::close_event(event);
+            return Err(e);
+        }
+
+        // Calibrate the TSC against the firmware's busy-wait; stall is accurate but blocking.
+        let t0 = unsafe { _rdtsc() };
+        boot::stall(CALIBRATION_US);
+        let t1 = unsafe { _rdtsc() };
+        let ticks = t1.wrapping_sub(t0);
+        if ticks == 0 {
+            let _ = boot::close_event(event);
+            return Err(uefi::Error::new(Status::UNSUPPORTED, ()));
+        }
+        let tsc_per_sec = ticks as f32 * (1_000_000.0 / CALIBRATION_US as f32);
+
+        Ok(Self {
+            event,
+            tsc_per_sec,
+            last_tsc: unsafe { _rdtsc() },
+        })
+    }
+
+    /// Seconds elapsed since the previous call (or since the clock was created).
+    #[inline]
+    pub fn tick(&mut self) -> f32 {
+        let now = unsafe { _rdtsc() };
+        let elapsed = now.wrapping_sub(self.last_tsc);
+        self.last_tsc = now;
+        elapsed as f32 / self.tsc_per_sec
+    }
+
+    /// Run `f` and return its result along with the seconds it took.
+    #[inline]
+    pub fn measure<R>(&self, f: impl FnOnce() -> R) -> (R, f32) {
+        let t0 = unsafe { _rdtsc() };
+        let result = f();
+        let t1 = unsafe { _rdtsc() };
+        (result, t1.wrapping_sub(t0) as f32 / self.tsc_per_sec)
+    }
+
+    /// Block until the next timer period elapses.
+    #[inline]
+    pub fn wait(&self) {
+        // SAFETY: the clone is only used for the duration of the wait; the event stays owned by us.
+        let mut events = [unsafe { self.event.unsafe_clone() }];
+        let _ = boot::wait_for_event(&mut events);
+    }
+}
+
+impl Drop for FrameClock {
+    fn drop(&mut self) {
+        // SAFETY: the event is not used after the clock is dropped.
+        let _ = boot::close_event(unsafe { self.event.unsafe_clone() });
+    }
+}
+
+/// Seed for the game RNG, taken from the time stamp counter while in boot services.
+#[inline]
+pub fn tsc_seed() -> u64 {
+    unsafe { _rdtsc() }
+}
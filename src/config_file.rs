@@ -0,0 +1,52 @@
+use crate::esp;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use ruefi_core::config::{self, Config};
+use ruefi_core::controls::Bindings;
+
+/// Name of the configuration file, looked up next to the game's executable.
+const CONFIG_FILE_NAME: &str = "ruefi.cfg";
+
+/// Load `ruefi.cfg` from the boot volume. A missing file yields the defaults; the returned
+/// messages describe malformed lines or read failures and should be shown to the player.
+pub fn load() -> (Config, Vec<String>) {
+    let path = path();
+    let data = match esp::read_file(&path) {
+        Ok(Some(data)) => data,
+        Ok(None) => return (Config::default(), Vec::new()),
+        Err(message) => return (Config::default(), alloc::vec![message]),
+    };
+    let Ok(text) = String::from_utf8(data) else {
+        return (
+            Config::default(),
+            alloc::vec![format!("{path}: file is not valid UTF-8")],
+        );
+    };
+
+    let (config, errors) = Config::parse(&text);
+    let messages = errors.iter().map(|e| format!("{path}: {e}")).collect();
+    (config, messages)
+}
+
+/// Write the key bindings to the `[controls]` section of `ruefi.cfg`, keeping all other lines.
+/// The file is created if it does not exist yet.
+pub fn save_controls(bindings: &Bindings) -> Result<(), String> {
+    let path = path();
+    let text = match esp::read_file(&path)? {
+        Some(data) => {
+            String::from_utf8(data).map_err(|_| format!("{path}: file is not valid UTF-8"))?
+        }
+        None => String::new(),
+    };
+    let text = config::replace_section(&text, "controls", &bindings.to_config());
+    esp::write_file(&path, text.as_bytes())
+}
+
+/// Path of `ruefi.cfg` on the boot volume.
+fn path() -> String {
+    format!(
+        "{}\\{CONFIG_FILE_NAME}",
+        esp::image_dir().unwrap_or_default()
+    )
+}
@@ -0,0 +1,61 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use uefi::boot;
+use uefi::fs::{FileSystem, PathBuf};
+use uefi::proto::device_path::DevicePathNodeEnum;
+use uefi::proto::loaded_image::LoadedImage;
+use uefi::{CString16, Status};
+
+/// Read a file from the volume the game was loaded from (usually the ESP);
+/// `None` if it does not exist. Errors are returned as displayable messages.
+pub fn read_file(path: &str) -> Result<Option<Vec<u8>>, String> {
+    let fs = boot::get_image_file_system(boot::image_handle())
+        .map_err(|e| format!("cannot open boot volume: {:?}", e.status()))?;
+    let file_path = CString16::try_from(path)
+        .map(PathBuf::from)
+        .map_err(|_| format!("{path}: invalid path"))?;
+
+    match FileSystem::new(fs).read(file_path) {
+        Ok(data) => Ok(Some(data)),
+        Err(uefi::fs::Error::Io(e)) if e.uefi_error.status() == Status::NOT_FOUND => Ok(None),
+        Err(uefi::fs::Error::Io(e)) => Err(format!(
+            "{path}: cannot read file ({:?})",
+            e.uefi_error.status()
+        )),
+        Err(e) => Err(format!("{path}: cannot read file ({e})")),
+    }
+}
+
+/// Write a file on the volume the game was loaded from, replacing its contents if it exists.
+pub fn write_file(path: &str, data: &[u8]) -> Result<(), String> {
+    let fs = boot::get_image_file_system(boot::image_handle())
+        .map_err(|e| format!("cannot open boot volume: {:?}", e.status()))?;
+    let file_path = CString16::try_from(path)
+        .map(PathBuf::from)
+        .map_err(|_| format!("{path}: invalid path"))?;
+    FileSystem::new(fs)
+        .write(file_path, data)
+        .map_err(|e| format!("{path}: cannot write file ({e})"))
+}
+
+/// Directory of the running executable on its volume, e.g. `\EFI\Boot`, taken from the
+/// file path nodes of the loaded image's device path.
+pub fn image_dir() -> Option<String> {
+    let image = boot::open_protocol_exclusive::<LoadedImage>(boot::image_handle()).ok()?;
+    let mut path = String::new();
+    for node in image.file_path()?.node_iter() {
+        if let Ok(DevicePathNodeEnum::MediaFilePath(file)) = node.as_enum() {
+            let name: String = char::decode_utf16(file.path_name())
+                .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+                .take_while(|&c| c != '\0')
+                .collect();
+            if !path.is_empty() && !path.ends_with('\\') && !name.starts_with('\\') {
+                path.push('\\');
+            }
+            path.push_str(&name);
+        }
+    }
+    let (dir, _) = path.rsplit_once('\\')?;
+    Some(dir.to_string())
+}
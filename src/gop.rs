@@ -0,0 +1,125 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use ruefi_render::blitter::{BackBuffer, PixelFormat};
+use ruefi_render::video::{self, VideoMode};
+use uefi::proto::console::gop::{self, BltOp, BltPixel, BltRegion, GraphicsOutput, Mode, ModeInfo};
+
+/// Translate a GOP mode's pixel layout; `None` if the mode has no linear framebuffer.
+pub fn pixel_format(info: &ModeInfo) -> Option<PixelFormat> {
+    match info.pixel_format() {
+        gop::PixelFormat::Rgb => Some(PixelFormat::Rgb),
+        gop::PixelFormat::Bgr => Some(PixelFormat::Bgr),
+        gop::PixelFormat::Bitmask => info.pixel_bitmask().map(|mask| PixelFormat::Bitmask {
+            red: mask.red,
+            green: mask.green,
+            blue: mask.blue,
+            reserved: mask.reserved,
+        }),
+        gop::PixelFormat::BltOnly => None,
+    }
+}
+
+/// Create a back buffer matching the current GOP mode's resolution and pixel format.
+pub fn back_buffer(gop: &GraphicsOutput) -> BackBuffer {
+    let info = gop.current_mode_info();
+    let (width, height) = info.resolution();
+    // Without framebuffer access, `blt` takes `BltPixel`s in blue, green, red, reserved order
+    let format = pixel_format(&info).unwrap_or(PixelFormat::Bgr);
+    BackBuffer::new(width, height, format)
+}
+
+/// Copy the parts of the back buffer that changed to the screen, either directly into the
+/// GOP framebuffer or, on `BltOnly` devices, through `GraphicsOutput::blt`.
+pub fn present(back: &BackBuffer, gop: &mut GraphicsOutput) {
+    let info = gop.current_mode_info();
+    if info.pixel_format() == gop::PixelFormat::BltOnly {
+        let pixels = back.pixels();
+        // SAFETY: `BltPixel` is four bytes (blue, green, red, reserved) with an alignment of one,
+        // which is exactly the layout of a `Bgr` back buffer.
+        let buffer = unsafe {
+            core::slice::from_raw_parts(pixels.as_ptr().cast::<BltPixel>(), pixels.len() / 4)
+        };
+        for rect in back.dirty_region().rects() {
+            let _ = gop.blt(BltOp::BufferToVideo {
+                buffer,
+                src: BltRegion::SubRectangle {
+                    coords: (rect.x0, rect.y0),
+                    px_stride: back.width,
+                },
+                dest: (rect.x0, rect.y0),
+                dims: (rect.width(), rect.height()),
+            });
+        }
+        return;
+    }
+
+    let dst_pitch = info.stride() * back.format.bytes_per_pixel();
+    let mut fb = gop.frame_buffer();
+    // SAFETY: the framebuffer is valid for `fb.size()` bytes while GOP is opened exclusively.
+    let dst = unsafe { core::slice::from_raw_parts_mut(fb.as_mut_ptr(), fb.size()) };
+    back.flush_to(dst, dst_pitch);
+}
+
+/// The video modes offered by the firmware, queried once at startup.
+pub struct ModeList {
+    modes: Vec<Mode>,
+    pub infos: Vec<VideoMode>, // same order as `modes`
+}
+
+impl ModeList {
+    pub fn query(gop: &GraphicsOutput) -> Self {
+        let modes: Vec<Mode> = gop.modes().collect();
+        let infos = modes
+            .iter()
+            .enumerate()
+            .map(|(index, mode)| {
+                let (width, height) = mode.info().resolution();
+                VideoMode {
+                    index,
+                    width,
+                    height,
+                    format: pixel_format(mode.info()),
+                }
+            })
+            .collect();
+        Self { modes, infos }
+    }
+
+    /// Index of the mode the display is currently in, if it is part of the list.
+    pub fn current(&self, gop: &GraphicsOutput) -> Option<usize> {
+        let info = gop.current_mode_info();
+        self.modes.iter().position(|m| *m.info() == info)
+    }
+
+    /// Index of the mode closest to the preferred resolution.
+    pub fn preferred(&self, width: usize, height: usize) -> Option<usize> {
+        video::preferred_mode(&self.infos, width, height).map(|m| m.index)
+    }
+
+    /// Switch the display to the mode at `index`. Invalidates the framebuffer,
+    /// so a new back buffer must be created afterwards.
+    pub fn set(&self, gop: &mut GraphicsOutput, index: usize) -> uefi::Result {
+        match self.modes.get(index) {
+            Some(mode) => gop.set_mode(mode),
+            None => Err(uefi::Status::INVALID_PARAMETER.into()),
+        }
+    }
+
+    /// Menu labels, e.g. `1280 x 720`; the current mode is marked with `*`,
+    /// modes without a linear 32 bpp framebuffer are marked as such.
+    pub fn labels(&self, current: Option<usize>) -> Vec<String> {
+        self.infos
+            .iter()
+            .map(|m| {
+                let kind = match m.format {
+                    Some(_) if m.is_linear_32bpp() => "",
+                    Some(_) => " (BITMASK)",
+                    None => " (BLT)",
+                };
+                let mark = if Some(m.index) == current { "*" } else { " " };
+                format!("{mark}{} x {}{kind}", m.width, m.height)
+            })
+            .collect()
+    }
+}
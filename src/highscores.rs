@@ -0,0 +1,25 @@
+use ruefi_core::highscore::HighScoreTable;
+use uefi::runtime::{self, VariableAttributes, VariableVendor};
+use uefi::{CStr16, cstr16, guid};
+
+/// Name of the UEFI variable holding the serialized high score table.
+const VARIABLE_NAME: &CStr16 = cstr16!("RuefiHighScores");
+
+/// Vendor GUID namespacing the game's UEFI variables.
+const VENDOR: VariableVendor = VariableVendor(guid!("f7a8bc22-0a1c-46f8-8bd6-0a541c955bdd"));
+
+/// Read the high score table from NVRAM; a missing or corrupt variable yields an empty table.
+pub fn load() -> HighScoreTable {
+    runtime::get_variable_boxed(VARIABLE_NAME, &VENDOR)
+        .ok()
+        .and_then(|(data, _)| HighScoreTable::from_bytes(&data))
+        .unwrap_or_default()
+}
+
+/// Write the high score table to a non-volatile variable so it survives reboots.
+pub fn save(table: &HighScoreTable) -> uefi::Result {
+    let attributes = VariableAttributes::NON_VOLATILE
+        | VariableAttributes::BOOTSERVICE_ACCESS
+        | VariableAttributes::RUNTIME_ACCESS;
+    runtime::set_variable(VARIABLE_NAME, &VENDOR, attributes, &table.to_bytes())
+}
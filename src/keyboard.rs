@@ -0,0 +1,150 @@
+//! Keyboard input via `EFI_SIMPLE_TEXT_INPUT_EX_PROTOCOL`, which reports modifier keys along
+//! with each keystroke. Neither `uefi` nor `uefi-raw` define it, so it is declared here;
+//! firmware without it falls back to the plain text input protocol.
+
+use core::ffi::c_void;
+use core::mem::MaybeUninit;
+use ruefi_core::input::{KeyId, Modifiers};
+use uefi::boot::{self, OpenProtocolAttributes, OpenProtocolParams, ScopedProtocol};
+use uefi::proto::console::text::{Input, Key, ScanCode};
+use uefi::proto::unsafe_protocol;
+use uefi::{Char16, Handle, Status};
+
+/// `EFI_KEY_STATE.KeyShiftState` bits.
+const SHIFT_STATE_VALID: u32 = 0x8000_0000;
+const RIGHT_SHIFT_PRESSED: u32 = 0x0000_0001;
+const LEFT_SHIFT_PRESSED: u32 = 0x0000_0002;
+const RIGHT_CONTROL_PRESSED: u32 = 0x0000_0004;
+const LEFT_CONTROL_PRESSED: u32 = 0x0000_0008;
+const RIGHT_ALT_PRESSED: u32 = 0x0000_0010;
+const LEFT_ALT_PRESSED: u32 = 0x0000_0020;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+struct RawKeyData {
+    scan_code: u16,
+    unicode_char: u16,
+    key_shift_state: u32, // modifier keys, valid if SHIFT_STATE_VALID is set
+    key_toggle_state: u8, // Caps/Num/Scroll Lock
+}
+
+// Only `read_key_stroke_ex` is called; the other members are declared for the layout.
+#[allow(dead_code)]
+#[repr(C)]
+#[derive(Debug)]
+struct SimpleTextInputExProtocol {
+    reset: unsafe extern "efiapi" fn(this: *mut Self, extended_verification: u8) -> Status,
+    read_key_stroke_ex: unsafe extern "efiapi" fn(this: *mut Self, key: *mut RawKeyData) -> Status,
+    wait_for_key_ex: *mut c_void,
+    set_state: unsafe extern "efiapi" fn(this: *mut Self, toggle_state: *const u8) -> Status,
+    register_key_notify: *mut c_void,
+    unregister_key_notify: *mut c_void,
+}
+
+/// The extended text input protocol.
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol("dd9e7534-7762-4698-8c14-f58517a625aa")]
+pub struct InputEx(SimpleTextInputExProtocol);
+
+impl InputEx {
+    /// The next keystroke with its modifier state, if any.
+    fn read_key(&mut self) -> Option<RawKeyData> {
+        let mut data = MaybeUninit::<RawKeyData>::uninit();
+        // SAFETY: the firmware fills in `data` on success.
+        match unsafe { (self.0.read_key_stroke_ex)(&mut self.0, data.as_mut_ptr()) } {
+            Status::SUCCESS => Some(unsafe { data.assume_init() }),
+            _ => None,
+        }
+    }
+}
+
+/// A keystroke as the game sees it.
+#[derive(Clone, Copy, Debug)]
+pub struct KeyEvent {
+    pub key: Key,
+    pub modifiers: Modifiers,
+}
+
+impl KeyEvent {
+    /// The physical key, for held-key tracking.
+    pub fn id(&self) -> KeyId {
+        match self.key {
+            Key::Special(scan) => KeyId::Scan(scan.0),
+            Key::Printable(c) => KeyId::char(char::from(c)),
+        }
+    }
+}
+
+/// Reads keystrokes from the console input, with modifiers where the firmware supports it.
+pub struct Keyboard {
+    ex: Option<ScopedProtocol<InputEx>>,
+}
+
+impl Keyboard {
+    /// Open the extended protocol on the console input handle, if available.
+    pub fn open() -> Self {
+        Self {
+            ex: open_input_ex(),
+        }
+    }
+
+    /// The next pending keystroke, if any. Both protocols share one queue on the console
+    /// handle, so only one of them is read.
+    pub fn read_event(&mut self, stdin: &mut Input) -> Option<KeyEvent> {
+        let Some(ex) = self.ex.as_mut() else {
+            let key = stdin.read_key().ok()??;
+            return Some(KeyEvent {
+                key,
+                modifiers: Modifiers::default(),
+            });
+        };
+        loop {
+            let data = ex.read_key()?;
+            // Partial keystrokes (a modifier on its own) carry neither scan code nor character
+            if data.scan_code == ScanCode::NULL.0 && data.unicode_char == 0 {
+                continue;
+            }
+            let key = if data.scan_code == ScanCode::NULL.0 {
+                Key::Printable(Char16::try_from(data.unicode_char).ok()?)
+            } else {
+                Key::Special(ScanCode(data.scan_code))
+            };
+            return Some(KeyEvent {
+                key,
+                modifiers: modifiers(data.key_shift_state),
+            });
+        }
+    }
+}
+
+fn modifiers(shift_state: u32) -> Modifiers {
+    if shift_state & SHIFT_STATE_VALID == 0 {
+        return Modifiers::default();
+    }
+    let any = |mask: u32| shift_state & mask != 0;
+    Modifiers {
+        shift: any(LEFT_SHIFT_PRESSED | RIGHT_SHIFT_PRESSED),
+        ctrl: any(LEFT_CONTROL_PRESSED | RIGHT_CONTROL_PRESSED),
+        alt: any(LEFT_ALT_PRESSED | RIGHT_ALT_PRESSED),
+    }
+}
+
+fn open_input_ex() -> Option<ScopedProtocol<InputEx>> {
+    let st = uefi::table::system_table_raw()?;
+    // SAFETY: the system table stays valid while boot services are active.
+    let handle = unsafe { Handle::from_ptr(st.as_ref().stdin_handle) }?;
+    // SAFETY: opened non-exclusively, like the firmware's own `Input` on the same handle;
+    // the text input protocols only share the keystroke queue.
+    unsafe {
+        boot::open_protocol::<InputEx>(
+            OpenProtocolParams {
+                handle,
+                agent: boot::image_handle(),
+                controller: None,
+            },
+            OpenProtocolAttributes::GetProtocol,
+        )
+    }
+    .ok()
+}
@@ -2,27 +2,48 @@
 #![no_main]
 #![allow(unsafe_code)]
 
-mod asteroid;
-mod blitter;
-mod projectile;
-mod rand;
-mod ship;
+mod assets;
+mod clock;
+mod config_file;
+mod esp;
+mod gop;
+mod highscores;
+mod keyboard;
+mod pointer;
 mod uefi_alloc;
+mod world;
 
-use crate::blitter::BackBuffer;
+use crate::assets::Assets;
+use crate::clock::{FrameClock, tsc_seed};
+use crate::keyboard::{KeyEvent, Keyboard};
+use crate::pointer::Pointers;
+use crate::world::World;
 extern crate alloc;
-use crate::asteroid::Asteroid;
-use crate::projectile::Projectile;
-use crate::rand::XorShift64;
-use crate::ship::Ship;
+use alloc::format;
+use alloc::string::String;
 use alloc::vec::Vec;
+use core::fmt::Write;
+use ruefi_core::controls::{Action, Bindings, ControlsCommand, ControlsMenu};
+use ruefi_core::highscore::{HighScore, InitialsEntry};
+use ruefi_core::input::{HeldKeys, InputState};
+use ruefi_core::pointer::{Button, Cursor};
+use ruefi_core::rand::XorShift64;
+use ruefi_core::state::{GameState, Menu, MenuItem, MenuPage, StateEvent};
+use ruefi_render::blitter::{BackBuffer, LineStyle, TextAlign};
+use ruefi_render::flush::{FlushPath, FrameStats};
+use ruefi_render::postfx::PostFx;
 use uefi::prelude::*;
 use uefi::proto::console::gop::GraphicsOutput;
-use uefi::proto::console::text::{Key, ScanCode};
+use uefi::proto::console::text::{Input, Key, ScanCode};
 
-mod logo {
-    include!(concat!(env!("OUT_DIR"), "/assets_gen.rs"));
-}
+/// Fixed simulation step in seconds.
+const SIM_DT: f32 = 1.0 / 60.0;
+
+/// Period of the frame pacing timer in units of 100 ns (~60 Hz).
+const FRAME_PERIOD_100NS: u64 = 10_000_000 / 60;
+
+/// Upper bound on the simulated time per rendered frame, in seconds.
+const MAX_FRAME_TIME: f32 = 0.25;
 
 #[entry]
 fn main() -> Status {
@@ -34,6 +55,12 @@ fn main() -> Status {
 }
 
 fn run_game() -> uefi::Result<()> {
+    // Tuning from `ruefi.cfg` and art from `assets` next to the executable;
+    // problems are shown once GOP is up
+    let (config, mut startup_errors) = config_file::load();
+    let (assets, asset_errors) = Assets::load();
+    startup_errors.extend(asset_errors);
+
     system::with_stdin(|stdin| {
         // Open GOP (scoped, exclusive) inside stdin closure
         let handle = boot::get_handle_for_protocol::<GraphicsOutput>()
@@ -41,127 +68,468 @@ fn run_game() -> uefi::Result<()> {
         let mut gop = boot::open_protocol_exclusive::<GraphicsOutput>(handle)
             .map_err(|_| uefi::Error::new(Status::ABORTED, ()))?;
 
+        // Switch to the preferred video mode; keep the firmware's mode if that fails
+        let modes = gop::ModeList::query(&gop);
+        if let Some(index) = modes.preferred(config.video_width, config.video_height)
+            && modes.current(&gop) != Some(index)
+        {
+            let _ = modes.set(&mut gop, index);
+        }
+
         let (sw, sh) = gop.current_mode_info().resolution();
-        let mut back = BackBuffer::from_gop(&mut gop);
+        let mut back = gop::back_buffer(&gop);
+
+        if !startup_errors.is_empty() {
+            show_startup_errors(stdin, &mut gop, &mut back, &startup_errors);
+        }
 
         // RNG seeded from a timing source
-        let mut rng = XorShift64::default();
+        let mut rng = XorShift64::new(tsc_seed());
 
-        // Asteroids: prepare a few, away from the center
-        let mut asteroids: Vec<Asteroid> = Vec::with_capacity(32);
-        for _ in 0..5 {
-            asteroids.push(Asteroid::random_spawn(&mut rng, sw, sh));
-        }
+        // Ship, asteroids, projectiles and score of the current game
+        let mut world = World::new(&config, sw, sh);
 
-        // Player ship
-        let mut ship = Ship::new(sw, sh);
+        // High score table persisted in NVRAM; filled in via the initials-entry screen after game over
+        let mut high_scores = highscores::load();
+        let mut initials: Option<InitialsEntry> = None;
+        let mut new_rank: Option<usize> = None;
+        let mut save_failed = false; // the NVRAM write of the last entry failed
 
-        // Projectiles
-        const MAX_PROJECTILES: usize = 100;
-        let mut projectiles: Vec<Projectile> = Vec::with_capacity(MAX_PROJECTILES);
-        let mut projectile_speed: f32 = 12.0; // configurable speed (pixels/frame)
-        let projectile_len: f32 = 5.0; // visible length in pixels
+        // Vector outlines of ship, asteroids and projectiles
+        let line_style = LineStyle {
+            width: config.line_width,
+            anti_aliased: config.anti_aliasing,
+        };
+
+        // Post-processing of the finished frame; each effect is toggled with P, B and L
+        let mut postfx = PostFx::new(config.persistence, config.bloom, config.scanlines);
+
+        // Frame-time overlay, toggled with F; while shown, flushes cycle through all copy paths
+        let mut stats = FrameStats::default();
+        let mut show_stats = false;
+
+        // Keys count as held until the firmware stops repeating them; the ship reads
+        // them as an input state every frame, menus ignore repeats where they would toggle
+        let mut keyboard = Keyboard::open();
+        let mut held = HeldKeys::default();
+        let mut bindings = config.controls.clone();
+
+        // Optional mouse or tablet: while in use, the ship aims at the cursor, the left button
+        // fires and the right button thrusts; in menus, it selects and clicks items
+        let mut pointers = Pointers::open();
+        let mut cursor = Cursor::new(sw, sh);
 
         // Cached screen size as f32
-        let sw_f: f32 = sw as f32;
-        let sh_f: f32 = sh as f32;
+        let mut sw_f: f32 = sw as f32;
+        let mut sh_f: f32 = sh as f32;
 
-        // Frame counter to optionally skip input polling every other frame
-        let mut frame: u64 = 0;
+        // Title → Playing ⇄ Paused → GameOver → Title; the title and pause screens share
+        // one menu, whose controls page edits the bindings
+        let mut state = GameState::default();
+        let mut menu = Menu::default();
+        let mut controls_menu = ControlsMenu::default();
+
+        // Fixed-timestep simulation: the timer paces rendering, the accumulator decides
+        // how many simulation steps are due since the last frame.
+        let mut clock = FrameClock::new(FRAME_PERIOD_100NS)?;
+        let mut accumulator: f32 = 0.0;
+        let mut pending_fire = false; // a shot requested in a frame without a simulation step
+        let mut pending_hyperspace = false; // likewise a hyperspace jump
 
         loop {
-            // Poll keyboard only on even frames to reduce overhead
-            let poll_input = (frame & 1) == 0;
-            // Derive intent for this frame
-            let mut rot: i8 = 0;
-            let mut thr: i8 = 0;
-            let mut fire: bool = false;
+            let prev_state = state;
+
+            // Handle key events of this frame; ship controls are derived from the held keys below
             let mut speed_adj: i8 = 0;
+            let mut open_page: Option<MenuPage> = None;
+            let mut activate: Option<usize> = None;
+            let mut back_out = false;
+            let mut resume = false;
+            let mut resume_key: Option<KeyEvent> = None;
+            let mut new_game = false;
+            let mut leave_game_over = false;
             let mut exit = false;
-            if poll_input {
-                while let Ok(Some(k)) = stdin.read_key() {
-                    match k {
-                        Key::Special(ScanCode::LEFT) => rot = -1,
-                        Key::Special(ScanCode::RIGHT) => rot = 1,
-                        Key::Special(ScanCode::UP) => thr = 1,
-                        Key::Special(ScanCode::DOWN) => thr = -1,
-                        // Space key as printable space
-                        Key::Printable(c) if c == ' ' => fire = true,
-                        // Adjust projectile speed with [ and ]
-                        Key::Printable(c) if c == '[' => {
-                            speed_adj = -1;
+            let mut apply_mode: Option<usize> = None;
+            let menu_len = match menu.page {
+                MenuPage::VideoMode => modes.infos.len(),
+                MenuPage::Controls => ControlsMenu::LEN,
+                _ => menu.items(state).len(),
+            };
+            while let Some(event) = keyboard.read_event(stdin) {
+                let k = event.key;
+                let id = event.id();
+                held.press(id, event.modifiers);
+                let first_press = held.just_pressed(id);
+                match state {
+                    GameState::Playing => {
+                        // Ship controls act while held; the other actions once per key press
+                        match bindings.action(id) {
+                            Some(Action::SpeedDown) => speed_adj = -1,
+                            Some(Action::SpeedUp) => speed_adj = 1,
+                            Some(Action::Hyperspace) => pending_hyperspace |= first_press,
+                            Some(Action::Pause) if first_press => open_page = Some(MenuPage::Main),
+                            _ => {}
                         }
-                        Key::Printable(c) if c == ']' => {
-                            speed_adj = 1;
+                        match k {
+                            // Pause on the controls screen with C and the video mode menu with V
+                            Key::Printable(c) if c == 'c' || c == 'C' => {
+                                open_page = Some(MenuPage::Controls);
+                            }
+                            Key::Printable(c) if c == 'v' || c == 'V' => {
+                                open_page = Some(MenuPage::VideoMode);
+                            }
+                            // Toggle phosphor persistence, bloom and scanlines
+                            Key::Printable(c) if (c == 'p' || c == 'P') && first_press => {
+                                postfx.persistence = !postfx.persistence;
+                            }
+                            Key::Printable(c) if (c == 'b' || c == 'B') && first_press => {
+                                postfx.bloom = !postfx.bloom;
+                            }
+                            Key::Printable(c) if (c == 'l' || c == 'L') && first_press => {
+                                postfx.scanlines = !postfx.scanlines;
+                            }
+                            Key::Printable(c) if (c == 'f' || c == 'F') && first_press => {
+                                show_stats = !show_stats;
+                                back.flush_path = FlushPath::detect();
+                            }
+                            Key::Special(ScanCode::ESCAPE) if first_press => {
+                                open_page = Some(MenuPage::Main);
+                            }
+                            _ => {}
+                        }
+                    }
+                    // Keys still held from the game must not type into the initials entry
+                    GameState::GameOver if held.is_ignored(id) => {}
+                    GameState::GameOver => match initials.as_mut() {
+                        Some(entry) => match k {
+                            Key::Special(ScanCode::UP) => entry.cycle(1),
+                            Key::Special(ScanCode::DOWN) => entry.cycle(-1),
+                            Key::Special(ScanCode::LEFT) => entry.move_cursor(-1),
+                            Key::Special(ScanCode::RIGHT) => entry.move_cursor(1),
+                            Key::Printable(c) if c == '\r' => {
+                                new_rank = high_scores.insert(HighScore {
+                                    score: world.score.points,
+                                    wave: world.waves.wave,
+                                    initials: entry.initials,
+                                });
+                                save_failed = highscores::save(&high_scores).is_err();
+                                initials = None;
+                            }
+                            Key::Printable(c) => {
+                                entry.type_char(char::from(c));
+                            }
+                            // Skip the entry; the score is not recorded
+                            Key::Special(ScanCode::ESCAPE) if first_press => initials = None,
+                            _ => {}
+                        },
+                        None => match k {
+                            Key::Printable(c) if c == '\r' => leave_game_over |= first_press,
+                            Key::Special(ScanCode::ESCAPE) => leave_game_over |= first_press,
+                            _ => {}
+                        },
+                    },
+                    GameState::Title | GameState::Paused => {
+                        if menu.page == MenuPage::Controls {
+                            match controls_menu.key(id, first_press, &mut bindings) {
+                                Some(ControlsCommand::Save) => {
+                                    save_controls(&mut controls_menu, &bindings);
+                                }
+                                Some(ControlsCommand::Close) => back_out |= first_press,
+                                None => {}
+                            }
+                            continue;
                         }
-                        Key::Special(ScanCode::ESCAPE) => {
-                            exit = true;
+                        match k {
+                            Key::Special(ScanCode::UP) => menu.move_selection(-1, menu_len),
+                            Key::Special(ScanCode::DOWN) => menu.move_selection(1, menu_len),
+                            Key::Printable(c) if c == '\r' && first_press => {
+                                activate = Some(menu.selected);
+                                resume_key = Some(event);
+                            }
+                            Key::Special(ScanCode::ESCAPE) if first_press => {
+                                back_out = true;
+                                resume_key = Some(event);
+                            }
+                            // The pause key resumes from anywhere in the pause menu
+                            _ if first_press
+                                && state == GameState::Paused
+                                && bindings.action(id) == Some(Action::Pause) =>
+                            {
+                                resume = true;
+                                resume_key = Some(event);
+                            }
+                            _ => {}
                         }
-                        _ => {}
                     }
                 }
             }
+
+            pointers.poll(&mut cursor, sw_f, sh_f);
+            if state.has_menu()
+                && !(menu.page == MenuPage::Controls && controls_menu.is_capturing())
+            {
+                let selected = match menu.page {
+                    MenuPage::Controls => &mut controls_menu.selected,
+                    _ => &mut menu.selected,
+                };
+                let hovered = back.menu_item_at(menu_len, *selected, cursor.y as isize);
+                if cursor.moved()
+                    && let Some(i) = hovered
+                {
+                    *selected = i;
+                }
+                if cursor.clicked(Button::Left) && hovered.is_some() {
+                    activate = hovered;
+                } else if cursor.clicked(Button::Right)
+                    || (cursor.clicked(Button::Left) && menu.page == MenuPage::HighScores)
+                {
+                    back_out = true;
+                }
+            }
+            if state == GameState::GameOver && initials.is_none() && cursor.clicked(Button::Left) {
+                leave_game_over = true;
+            }
+
+            // Carry out the chosen menu entry
+            if let Some(i) = activate {
+                match menu.page {
+                    MenuPage::Controls => {
+                        controls_menu.selected = i;
+                        if controls_menu.activate(&mut bindings) == Some(ControlsCommand::Save) {
+                            save_controls(&mut controls_menu, &bindings);
+                        }
+                    }
+                    MenuPage::VideoMode => apply_mode = Some(i),
+                    MenuPage::HighScores => back_out = true,
+                    MenuPage::Main | MenuPage::Settings => match menu.items(state).get(i) {
+                        Some(MenuItem::Play | MenuItem::Restart) => new_game = true,
+                        Some(MenuItem::Resume) => resume = true,
+                        Some(MenuItem::Settings) => open_page = Some(MenuPage::Settings),
+                        Some(MenuItem::HighScores) => open_page = Some(MenuPage::HighScores),
+                        Some(MenuItem::ExitToFirmware) => exit = true,
+                        Some(MenuItem::Controls) => open_page = Some(MenuPage::Controls),
+                        Some(MenuItem::VideoMode) => open_page = Some(MenuPage::VideoMode),
+                        Some(MenuItem::Persistence) => postfx.persistence = !postfx.persistence,
+                        Some(MenuItem::Bloom) => postfx.bloom = !postfx.bloom,
+                        Some(MenuItem::Scanlines) => postfx.scanlines = !postfx.scanlines,
+                        Some(MenuItem::Back) => back_out = true,
+                        None => {}
+                    },
+                }
+            }
             if exit {
                 break;
             }
-
-            // Update ship physics and wrapping
-            ship.update(rot, thr, sw_f, sh_f);
-
-            // Fire projectile if requested and under cap
-            if fire && projectiles.len() < MAX_PROJECTILES {
-                projectiles.push(Projectile::spawn_from_ship(&ship, projectile_speed));
+            // Leaving the pause menu's main page resumes the game
+            resume |= back_out && !menu.back(state);
+            if resume {
+                state = state.next(StateEvent::Resume);
             }
 
-            // Apply projectile speed adjustments
-            if speed_adj != 0 {
-                projectile_speed = (projectile_speed + (speed_adj as f32)).clamp(2.0, 50.0);
+            if let Some(page) = open_page {
+                // Pausing from the game opens the menu, possibly directly on a page
+                state = state.next(StateEvent::Pause);
+                if page == MenuPage::Controls {
+                    controls_menu = ControlsMenu::default();
+                }
+                let selected = if page == MenuPage::VideoMode {
+                    modes.current(&gop).unwrap_or(0)
+                } else {
+                    0
+                };
+                menu.open(page, selected);
             }
-
-            // Update projectiles
-            for p in &mut projectiles {
-                p.update();
+            if new_game {
+                world.restart(&config);
+                (initials, new_rank, save_failed) = (None, None, false);
+                state = state.next(StateEvent::NewGame);
+            }
+            if leave_game_over {
+                state = state.next(StateEvent::Continue);
+                menu = Menu::default();
+            }
+            if state == GameState::Playing && prev_state != GameState::Playing {
+                // Just started or resumed: forget keys held in the menu, except the one that
+                // closed it, so that its key repeats do not count as new presses
+                menu = Menu::default();
+                held.clear();
+                pending_fire = false;
+                pending_hyperspace = false;
+                world.cancel_input();
+                if let Some(event) = resume_key {
+                    held.press(event.id(), event.modifiers);
+                }
             }
 
-            // Cull projectiles that left the screen
-            projectiles.retain(|p| p.on_screen(sw_f, sh_f));
-
-            // Update asteroids
-            for a in &mut asteroids {
-                a.update(sw_f, sh_f);
+            // Switch video modes; the world is scaled along with the screen
+            if let Some(index) = apply_mode
+                && modes.set(&mut gop, index).is_ok()
+            {
+                let (w, h) = gop.current_mode_info().resolution();
+                cursor.x *= w as f32 / sw_f;
+                cursor.y *= h as f32 / sh_f;
+                world.resize(w, h);
+                (sw_f, sh_f) = (w as f32, h as f32);
+                back = gop::back_buffer(&gop);
+                menu.back(state);
             }
 
-            // Asteroid-asteroid collisions handled by Asteroid helper
-            Asteroid::resolve_collisions(&mut asteroids, sw_f, sh_f);
+            if state.simulates() {
+                if speed_adj != 0 {
+                    world.adjust_projectile_speed(speed_adj);
+                }
 
-            // Projectile vs Asteroid collisions and splitting handled by Asteroid helper
-            Asteroid::handle_projectile_collisions(
-                &mut asteroids,
-                &mut projectiles,
-                &mut rng,
-                sw_f,
-                sh_f,
-            );
+                // Clamp long frames (e.g. firmware hiccups) so the simulation can catch up
+                let frame_time = clock.tick();
+                stats.record_frame(frame_time);
+                let mut input = InputState::from_keys(&held, &bindings);
+                held.tick(frame_time);
+                // Taps and clicks are reported for a single frame; keep them until a step is due
+                pending_fire |= input.fire;
+                let aim = cursor.is_active();
+                if aim {
+                    pending_fire |= cursor.clicked(Button::Left);
+                    input.fire |= cursor.is_down(Button::Left);
+                    if input.thrust == 0 && cursor.is_down(Button::Right) {
+                        input.thrust = 1;
+                    }
+                }
+                cursor.tick(frame_time);
+                accumulator += frame_time.min(MAX_FRAME_TIME);
+                while accumulator >= SIM_DT {
+                    accumulator -= SIM_DT;
+                    input.fire |= core::mem::take(&mut pending_fire);
+                    let hyperspace = core::mem::take(&mut pending_hyperspace);
+                    world.step(&input, aim.then_some(&cursor), hyperspace, &mut rng, SIM_DT);
+                }
+
+                // Ask for initials once per game if the final score makes the table
+                if state == GameState::Playing && world.ship.is_game_over() {
+                    state = state.next(StateEvent::ShipsLost);
+                    held.ignore_held();
+                    if high_scores.qualifies(world.score.points) {
+                        initials = Some(InitialsEntry::default());
+                    }
+                }
+            } else {
+                // Paused: discard the elapsed time so the game resumes where it left off
+                let frame_time = clock.tick();
+                held.tick(frame_time);
+                cursor.tick(frame_time);
+            }
 
             // Double-buffered rendering: clear backbuffer, compose scene, then flush
             back.clear_bgr(0, 0, 0);
-            back.blit_rgba(logo::LOGO_RGBA, logo::LOGO_WIDTH, logo::LOGO_HEIGHT, 10, 10);
+            back.blit_rgba(
+                &assets.logo.rgba,
+                assets.logo.width,
+                assets.logo.height,
+                10,
+                10,
+                255,
+            );
+            if state.simulates() {
+                world.draw(&mut back, &assets, line_style);
 
-            for a in &asteroids {
-                back.draw_asteroid_wrapped(a, sw, sh, 200, 200, 200);
-            }
+                if state == GameState::GameOver {
+                    let (cx, cy) = (back.width as isize / 2, back.height as isize / 2);
+                    back.draw_text(
+                        "GAME OVER",
+                        cx,
+                        cy - 16,
+                        4,
+                        TextAlign::Center,
+                        255,
+                        255,
+                        255,
+                    );
+                    match &initials {
+                        Some(entry) => back.draw_initials_entry(entry, world.score.points, cy + 32),
+                        None => {
+                            back.draw_high_scores(&high_scores, new_rank, cy + 32);
+                            if save_failed {
+                                back.draw_text(
+                                    "COULD NOT SAVE HIGH SCORES",
+                                    cx,
+                                    back.height as isize - 32,
+                                    1,
+                                    TextAlign::Center,
+                                    255,
+                                    80,
+                                    80,
+                                );
+                            }
+                            back.draw_text(
+                                "PRESS ENTER TO CONTINUE",
+                                cx,
+                                back.height as isize - 16,
+                                1,
+                                TextAlign::Center,
+                                140,
+                                140,
+                                140,
+                            );
+                        }
+                    }
+                }
 
-            for p in &projectiles {
-                back.draw_projectile(p.x, p.y, p.vx, p.vy, projectile_len, 255, 255, 0);
+                postfx.apply(&mut back);
+            } else {
+                match menu.page {
+                    MenuPage::HighScores => {
+                        back.draw_menu::<&str>("", &[], 0, "ENTER OR ESC BACK");
+                        back.draw_high_scores(&high_scores, new_rank, back.height as isize / 4);
+                    }
+                    MenuPage::Controls => back.draw_menu(
+                        "CONTROLS",
+                        &controls_menu.items(&bindings),
+                        controls_menu.selected,
+                        controls_menu.footer(),
+                    ),
+                    MenuPage::VideoMode => back.draw_menu(
+                        "VIDEO MODE",
+                        &modes.labels(modes.current(&gop)),
+                        menu.selected,
+                        "UP/DOWN SELECT  ENTER APPLY  ESC BACK",
+                    ),
+                    MenuPage::Main | MenuPage::Settings => {
+                        let (title, footer) = match (menu.page, state) {
+                            (MenuPage::Settings, _) => {
+                                ("SETTINGS", "UP/DOWN SELECT  ENTER CHOOSE  ESC BACK")
+                            }
+                            (_, GameState::Paused) => {
+                                ("PAUSED", "UP/DOWN SELECT  ENTER CHOOSE  ESC RESUME")
+                            }
+                            _ => ("RUEFI", "UP/DOWN SELECT  ENTER CHOOSE"),
+                        };
+                        let labels = menu_labels(menu.items(state), &postfx);
+                        back.draw_menu(title, &labels, menu.selected, footer);
+                    }
+                }
             }
 
-            back.draw_ship(&ship, 92, 127, 255);
-
-            back.flush_to_gop(&mut gop);
+            // Drawn after post-processing so that it stays sharp
+            if cursor.is_active() {
+                back.draw_cursor(cursor.x as isize, cursor.y as isize);
+            }
+            if show_stats {
+                back.draw_frame_stats(&stats);
+                // Benchmark: use the next supported path for each frame
+                let current = back.flush_path;
+                back.flush_path = FlushPath::ALL
+                    .into_iter()
+                    .filter(|p| p.is_supported())
+                    .skip_while(|&p| p != current)
+                    .nth(1)
+                    .unwrap_or(FlushPath::Scalar);
+                let ((), seconds) = clock.measure(|| gop::present(&back, &mut gop));
+                stats.record_flush(back.flush_path, seconds);
+            } else {
+                gop::present(&back, &mut gop);
+            }
 
-            // Simple frame pacing (~60 FPS): stall for ~16 ms to reduce CPU usage and tearing
-            boot::stall(16_000);
-            frame = frame.wrapping_add(1);
+            // Sleep until the next timer period; simulation time is tracked by the clock
+            clock.wait();
         }
 
         // On exit, print a message (may or may not be visible depending on GOP/console state)
@@ -173,3 +541,65 @@ fn run_game() -> uefi::Result<()> {
         Ok(())
     })
 }
+
+/// Menu entries as shown, with the state of the effects they toggle.
+fn menu_labels(items: &[MenuItem], postfx: &PostFx) -> Vec<String> {
+    items
+        .iter()
+        .map(|&item| {
+            let enabled = match item {
+                MenuItem::Persistence => Some(postfx.persistence),
+                MenuItem::Bloom => Some(postfx.bloom),
+                MenuItem::Scanlines => Some(postfx.scanlines),
+                _ => None,
+            };
+            match enabled {
+                Some(true) => format!("{}: ON", item.label()),
+                Some(false) => format!("{}: OFF", item.label()),
+                None => String::from(item.label()),
+            }
+        })
+        .collect()
+}
+
+/// Write the key bindings to `ruefi.cfg` and show the outcome on the controls screen.
+fn save_controls(menu: &mut ControlsMenu, bindings: &Bindings) {
+    menu.status = Some(match config_file::save_controls(bindings) {
+        Ok(()) => "SAVED TO RUEFI.CFG",
+        Err(_) => "COULD NOT WRITE RUEFI.CFG",
+    });
+}
+
+/// Show the problems found in `ruefi.cfg` or the assets until a key is pressed;
+/// the game then starts with the defaults for the affected settings and images.
+fn show_startup_errors(
+    stdin: &mut Input,
+    gop: &mut GraphicsOutput,
+    back: &mut BackBuffer,
+    errors: &[String],
+) {
+    let cx = back.width as isize / 2;
+    back.clear_bgr(0, 0, 0);
+    back.draw_text("STARTUP ERRORS", cx, 20, 3, TextAlign::Center, 255, 80, 80);
+    let mut text = String::new();
+    for e in errors {
+        let _ = writeln!(text, "{e}");
+    }
+    back.draw_text(&text, 20, 60, 1, TextAlign::Left, 255, 255, 255);
+    back.draw_text(
+        "PRESS ANY KEY TO CONTINUE WITH DEFAULTS",
+        cx,
+        back.height as isize - 30,
+        2,
+        TextAlign::Center,
+        200,
+        200,
+        200,
+    );
+    gop::present(back, gop);
+
+    if let Some(event) = stdin.wait_for_key_event() {
+        let _ = boot::wait_for_event(&mut [event]);
+    }
+    let _ = stdin.read_key();
+}
@@ -0,0 +1,135 @@
+//! Mouse and tablet input via the Simple Pointer (relative) and Absolute Pointer protocols.
+//! `uefi` only wraps the former, so the latter is wrapped here around its `uefi-raw` definition.
+//! Both are polled if present: firmware often exposes an (idle) absolute pointer on the
+//! console handle even when only a relative mouse is attached.
+
+use core::mem::MaybeUninit;
+use ruefi_core::pointer::Cursor;
+use uefi::Handle;
+use uefi::boot::{self, OpenProtocolAttributes, OpenProtocolParams, ScopedProtocol};
+use uefi::proto::ProtocolPointer;
+use uefi::proto::console::pointer::Pointer;
+use uefi::proto::unsafe_protocol;
+use uefi::{Status, StatusExt};
+use uefi_raw::protocol::console::{
+    AbsolutePointerMode, AbsolutePointerProtocol, AbsolutePointerState,
+};
+
+/// Screen pixels per millimeter of relative pointer movement.
+const PIXELS_PER_MM: f32 = 6.0;
+
+/// `AbsolutePointerState::active_buttons` bits: touch or primary button, alternate button.
+const TOUCH_ACTIVE: u32 = 0x1;
+const ALT_ACTIVE: u32 = 0x2;
+
+/// The absolute pointer protocol, e.g. of a tablet or touch screen.
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(AbsolutePointerProtocol::GUID)]
+pub struct AbsolutePointer(AbsolutePointerProtocol);
+
+impl AbsolutePointer {
+    /// The device's state, if it changed since the last call.
+    fn read_state(&mut self) -> uefi::Result<Option<AbsolutePointerState>> {
+        let mut state = MaybeUninit::<AbsolutePointerState>::uninit();
+        // SAFETY: the firmware fills in `state` on success.
+        match unsafe { (self.0.get_state)(&self.0, state.as_mut_ptr()) } {
+            Status::NOT_READY => Ok(None),
+            other => other.to_result_with_val(|| Some(unsafe { state.assume_init() })),
+        }
+    }
+
+    fn mode(&self) -> AbsolutePointerMode {
+        // SAFETY: the firmware keeps the mode valid for the lifetime of the protocol.
+        unsafe { *self.0.mode }
+    }
+}
+
+/// All pointer devices found at startup.
+pub struct Pointers {
+    absolute: Option<ScopedProtocol<AbsolutePointer>>,
+    relative: Option<ScopedProtocol<Pointer>>,
+}
+
+impl Pointers {
+    /// Open whichever pointer protocols the firmware exposes.
+    pub fn open() -> Self {
+        Self {
+            absolute: open_shared(),
+            relative: open_shared(),
+        }
+    }
+
+    /// Apply all movement and button changes since the last poll to the cursor.
+    pub fn poll(&mut self, cursor: &mut Cursor, sw: f32, sh: f32) {
+        if let Some(pointer) = self.relative.as_mut() {
+            let resolution = pointer.mode().resolution;
+            while let Ok(Some(state)) = pointer.read_state() {
+                // A resolution of 0 marks an unsupported axis
+                let scale = |counts: i32, per_mm: u64| match per_mm {
+                    0 => 0.0,
+                    _ => counts as f32 * PIXELS_PER_MM / per_mm as f32,
+                };
+                let dx = scale(state.relative_movement[0], resolution[0]);
+                let dy = scale(state.relative_movement[1], resolution[1]);
+                cursor.move_by(dx, dy, sw, sh);
+                cursor.set_buttons(state.button);
+            }
+        }
+        if let Some(pointer) = self.absolute.as_mut() {
+            let mode = pointer.mode();
+            if mode.absolute_max_x <= mode.absolute_min_x
+                || mode.absolute_max_y <= mode.absolute_min_y
+            {
+                return;
+            }
+            while let Ok(Some(state)) = pointer.read_state() {
+                let map = |v: u64, min: u64, max: u64, size: f32| {
+                    v.saturating_sub(min) as f32 / (max - min) as f32 * size
+                };
+                let x = map(
+                    state.current_x,
+                    mode.absolute_min_x,
+                    mode.absolute_max_x,
+                    sw,
+                );
+                let y = map(
+                    state.current_y,
+                    mode.absolute_min_y,
+                    mode.absolute_max_y,
+                    sh,
+                );
+                cursor.move_to(x, y, sw, sh);
+                cursor.set_buttons([
+                    state.active_buttons & TOUCH_ACTIVE != 0,
+                    state.active_buttons & ALT_ACTIVE != 0,
+                ]);
+            }
+        }
+    }
+}
+
+/// Open a protocol non-exclusively, preferring the console input handle, where the firmware
+/// merges all devices of a kind, over the first device handle.
+fn open_shared<P: ProtocolPointer + ?Sized>() -> Option<ScopedProtocol<P>> {
+    let open = |handle: Handle| {
+        // SAFETY: opened without exclusive access, so the console splitter keeps working.
+        unsafe {
+            boot::open_protocol::<P>(
+                OpenProtocolParams {
+                    handle,
+                    agent: boot::image_handle(),
+                    controller: None,
+                },
+                OpenProtocolAttributes::GetProtocol,
+            )
+        }
+        .ok()
+    };
+    let console = uefi::table::system_table_raw()
+        // SAFETY: the system table stays valid while boot services are active.
+        .and_then(|st| unsafe { Handle::from_ptr(st.as_ref().stdin_handle) });
+    console
+        .and_then(open)
+        .or_else(|| open(boot::get_handle_for_protocol::<P>().ok()?))
+}
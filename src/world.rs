@@ -0,0 +1,268 @@
+//! Everything that makes up one game: the ship, asteroids, projectiles and effects,
+//! with the fixed-timestep simulation and rendering of the playfield.
+
+use crate::assets::Assets;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+use ruefi_core::asteroid::Asteroid;
+use ruefi_core::config::{Config, PROJECTILE_SPEED_MAX, PROJECTILE_SPEED_MIN};
+use ruefi_core::input::InputState;
+use ruefi_core::particle::ParticlePool;
+use ruefi_core::pointer::Cursor;
+use ruefi_core::projectile::Projectile;
+use ruefi_core::rand::XorShift64;
+use ruefi_core::score::Score;
+use ruefi_core::ship::{SPAWN_CLEAR_RADIUS, Ship};
+use ruefi_core::wave::WaveManager;
+use ruefi_render::blitter::{BackBuffer, LineStyle, Sampling, TextAlign};
+
+/// Seconds between shots while the fire key is held.
+const FIRE_INTERVAL: f32 = 0.12;
+
+/// Projectile speed adjustment step for `[` and `]`, in pixels/second.
+const PROJECTILE_SPEED_STEP: f32 = 60.0;
+
+pub struct World {
+    pub ship: Ship,
+    pub asteroids: Vec<Asteroid>, // spawned by the wave manager whenever the field is empty
+    pub waves: WaveManager,
+    pub score: Score,
+    projectiles: Vec<Projectile>,
+    particles: ParticlePool, // asteroid debris, ship explosions and thruster exhaust
+    max_projectiles: usize,
+    projectile_speed: f32, // adjustable in game
+    projectile_len: f32,   // visible length in pixels
+    fire_cooldown: f32,    // seconds until the next shot
+    fire_queued: bool,     // fire requested during the cooldown
+    sw: usize,             // screen size in pixels
+    sh: usize,
+}
+
+impl World {
+    /// A new game on a screen of `sw` × `sh` pixels; the first wave is announced right away.
+    pub fn new(config: &Config, sw: usize, sh: usize) -> Self {
+        Self {
+            ship: config.ship(sw, sh),
+            asteroids: Vec::with_capacity(32),
+            waves: WaveManager::new(config.waves.clone()),
+            score: Score::default(),
+            projectiles: Vec::with_capacity(config.max_projectiles),
+            particles: ParticlePool::default(),
+            max_projectiles: config.max_projectiles,
+            projectile_speed: config.projectile_speed,
+            projectile_len: config.projectile_len,
+            fire_cooldown: 0.0,
+            fire_queued: false,
+            sw,
+            sh,
+        }
+    }
+
+    /// Start over, keeping the projectile speed chosen in game.
+    pub fn restart(&mut self, config: &Config) {
+        let projectile_speed = self.projectile_speed;
+        *self = Self::new(config, self.sw, self.sh);
+        self.projectile_speed = projectile_speed;
+    }
+
+    /// Forget requests made before a menu was opened, such as a shot waiting for the cooldown.
+    pub fn cancel_input(&mut self) {
+        self.fire_queued = false;
+    }
+
+    /// Change the projectile speed by `steps` increments.
+    pub fn adjust_projectile_speed(&mut self, steps: i8) {
+        self.projectile_speed = (self.projectile_speed + steps as f32 * PROJECTILE_SPEED_STEP)
+            .clamp(PROJECTILE_SPEED_MIN, PROJECTILE_SPEED_MAX);
+    }
+
+    /// Scale all positions to a new screen size; particles are dropped.
+    pub fn resize(&mut self, sw: usize, sh: usize) {
+        let fx = sw as f32 / self.sw as f32;
+        let fy = sh as f32 / self.sh as f32;
+        (self.sw, self.sh) = (sw, sh);
+        self.ship.x *= fx;
+        self.ship.y *= fy;
+        for a in &mut self.asteroids {
+            a.x *= fx;
+            a.y *= fy;
+        }
+        for p in &mut self.projectiles {
+            p.x *= fx;
+            p.y *= fy;
+        }
+        self.particles.clear();
+    }
+
+    /// Advance the game by `dt` seconds. While `aim` is given, the ship turns toward
+    /// the cursor unless rotation keys are held.
+    pub fn step(
+        &mut self,
+        input: &InputState,
+        aim: Option<&Cursor>,
+        hyperspace: bool,
+        rng: &mut XorShift64,
+        dt: f32,
+    ) {
+        let (sw, sh) = (self.sw, self.sh);
+        let (sw_f, sh_f) = (sw as f32, sh as f32);
+        let ship = &mut self.ship;
+
+        // Update ship physics and wrapping; a destroyed ship waits for a clear spawn area
+        ship.tick_timers(dt);
+        if ship.alive && hyperspace {
+            ship.hyperspace(rng, sw_f, sh_f);
+        }
+        if ship.alive {
+            // Arrow keys take precedence over the pointer
+            let rotate = match (input.rotate, aim) {
+                (0, Some(cursor)) => cursor.steer(ship, dt),
+                (rotate, _) => rotate,
+            };
+            ship.update(rotate, input.thrust, dt, sw_f, sh_f);
+            if input.thrust > 0 {
+                self.particles.exhaust(ship, rng);
+            }
+        } else if ship.ready_to_respawn()
+            && Asteroid::area_clear(
+                &self.asteroids,
+                sw_f * 0.5,
+                sh_f * 0.5,
+                SPAWN_CLEAR_RADIUS,
+                sw_f,
+                sh_f,
+            )
+        {
+            ship.respawn(sw, sh);
+        }
+
+        // Fire projectile if requested, reloaded and under cap; a request made while
+        // reloading fires as soon as the cooldown ends
+        self.fire_cooldown = (self.fire_cooldown - dt).max(0.0);
+        self.fire_queued |= input.fire;
+        if self.fire_queued && self.fire_cooldown == 0.0 {
+            self.fire_queued = false;
+            if ship.alive && self.projectiles.len() < self.max_projectiles {
+                self.projectiles
+                    .push(Projectile::spawn_from_ship(ship, self.projectile_speed));
+                self.fire_cooldown = FIRE_INTERVAL;
+            }
+        }
+
+        // Update projectiles and cull those that left the screen
+        for p in &mut self.projectiles {
+            p.update(dt);
+        }
+        self.projectiles.retain(|p| p.on_screen(sw_f, sh_f));
+
+        // Update asteroids
+        for a in &mut self.asteroids {
+            a.update(dt, sw_f, sh_f);
+        }
+
+        // Asteroid-asteroid collisions handled by Asteroid helper
+        Asteroid::resolve_collisions(&mut self.asteroids, sw_f, sh_f);
+
+        // Projectile vs Asteroid collisions and splitting handled by Asteroid helper
+        let hits = Asteroid::handle_projectile_collisions(
+            &mut self.asteroids,
+            &mut self.projectiles,
+            rng,
+            sw_f,
+            sh_f,
+        );
+        self.score.award(&hits);
+        for hit in &hits {
+            self.particles
+                .asteroid_debris(hit.x, hit.y, hit.radius, rng);
+        }
+
+        // Ship vs Asteroid collisions; costs a life unless the ship is invulnerable
+        if Asteroid::handle_ship_collision(&mut self.asteroids, &mut self.ship, rng, sw_f, sh_f) {
+            self.particles.ship_explosion(&self.ship, rng);
+        }
+
+        self.particles.update(dt, sw_f, sh_f);
+
+        // Announce and spawn the next wave once the field is cleared
+        self.waves.update(dt, &mut self.asteroids, rng, sw, sh);
+    }
+
+    /// Draw the playfield and HUD. Bitmap art from the assets folder is used if present,
+    /// vector outlines otherwise.
+    pub fn draw(&self, back: &mut BackBuffer, assets: &Assets, line_style: LineStyle) {
+        let (sw, sh) = (back.width, back.height);
+        for a in &self.asteroids {
+            match &assets.asteroid {
+                Some(art) => {
+                    let scale = 2.0 * a.radius / art.width.max(art.height) as f32;
+                    back.draw_sprite_wrapped(
+                        art,
+                        a.x,
+                        a.y,
+                        a.base_angle,
+                        scale,
+                        Sampling::Bilinear,
+                        255,
+                        sw,
+                        sh,
+                    );
+                }
+                None => {
+                    back.fill_asteroid_wrapped(a, sw, sh, 60, 60, 60);
+                    back.draw_asteroid_wrapped(a, sw, sh, line_style, 200, 200, 200);
+                }
+            }
+        }
+
+        back.draw_particles(self.particles.particles());
+
+        for p in &self.projectiles {
+            back.draw_projectile(
+                p.x,
+                p.y,
+                p.vx,
+                p.vy,
+                self.projectile_len,
+                line_style,
+                255,
+                255,
+                0,
+            );
+        }
+
+        let ship = &self.ship;
+        if ship.is_visible() {
+            match &assets.ship {
+                Some(art) => {
+                    // The art points up, the ship's angle 0 faces down
+                    let scale = 2.0 * ship.tri_h / art.height as f32;
+                    let angle = ship.angle + core::f32::consts::PI;
+                    back.draw_sprite(art, ship.x, ship.y, angle, scale, Sampling::Bilinear, 255);
+                }
+                None => {
+                    back.fill_ship(ship, 30, 45, 110);
+                    back.draw_ship(ship, line_style, 92, 127, 255);
+                }
+            }
+        }
+
+        back.draw_hud(self.score.points, ship.lives, self.waves.wave);
+
+        if let Some(wave) = self.waves.banner() {
+            let mut banner = String::new();
+            let _ = write!(banner, "WAVE {wave}");
+            back.draw_text(
+                &banner,
+                sw as isize / 2,
+                sh as isize / 3,
+                4,
+                TextAlign::Center,
+                255,
+                255,
+                255,
+            );
+        }
+    }
+}